@@ -0,0 +1,110 @@
+use cozy_chess::*;
+use crate::eval::{EvalBreakdown, GeotensorEvaluator};
+
+/// Static evaluation before and after one move of a game.
+#[derive(Clone, Copy, Debug)]
+pub struct MoveDelta {
+    pub ply: usize,
+    pub mv: Move,
+    /// Breakdown with the mover to move
+    pub before: EvalBreakdown,
+    /// Breakdown after the move, with the opponent to move
+    pub after: EvalBreakdown,
+    /// Change in the mover's total eval from before this move to before their
+    /// next one, so a piece left hanging shows up once the reply takes it
+    /// (negative = worsened). The last move of each side falls back to `after`.
+    pub swing: i32,
+}
+
+/// Replays a game and attributes the eval change of every move to its components.
+pub struct Analyzer {
+    pub evaluator: GeotensorEvaluator,
+}
+
+impl Analyzer {
+    pub fn new(evaluator: GeotensorEvaluator) -> Self {
+        Self { evaluator }
+    }
+
+    /// One delta per move until the game ends or a move is illegal.
+    pub fn analyze(&mut self, start: &Board, moves: &[Move]) -> Vec<MoveDelta> {
+        let mut board = start.clone();
+        // positions[i] is the breakdown before move i (and after the last move at the end)
+        let mut positions = vec![self.evaluator.breakdown(&board)];
+        let mut played = Vec::with_capacity(moves.len());
+        for &mv in moves {
+            if !board.is_legal(mv) {
+                break;
+            }
+            board.play(mv);
+            played.push(mv);
+            positions.push(self.evaluator.breakdown(&board));
+        }
+
+        played
+            .iter()
+            .enumerate()
+            .map(|(ply, &mv)| {
+                let before = positions[ply];
+                let after = positions[ply + 1];
+                let mover_later = match positions.get(ply + 2) {
+                    Some(next_turn) => next_turn.total,
+                    None => -after.total,
+                };
+                MoveDelta { ply, mv, before, after, swing: mover_later - before.total }
+            })
+            .collect()
+    }
+}
+
+/// The move that hurt its own side most, if any move lost eval at all.
+pub fn largest_negative_swing(deltas: &[MoveDelta]) -> Option<&MoveDelta> {
+    deltas.iter().filter(|d| d.swing < 0).min_by_key(|d| d.swing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn moves(uci: &[&str]) -> Vec<Move> {
+        uci.iter().map(|mv| mv.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn hanging_the_queen_is_the_largest_negative_swing() {
+        let mut analyzer = Analyzer::new(GeotensorEvaluator::new(None));
+        // 2. Qg4?? leaves the queen to the c8 bishop
+        let deltas = analyzer.analyze(&Board::default(), &moves(&["e2e4", "d7d5", "d1g4", "c8g4", "e4d5"]));
+        assert_eq!(deltas.len(), 5);
+        let blunder = largest_negative_swing(&deltas).unwrap();
+        assert_eq!((blunder.ply, blunder.mv.to_string()), (2, "d1g4".to_string()));
+    }
+
+    #[test]
+    fn deltas_chain_and_swings_sum_per_side() {
+        let mut analyzer = Analyzer::new(GeotensorEvaluator::new(None));
+        let game = moves(&["e2e4", "e7e5", "g1f3", "b8c6", "f1b5", "a7a6", "b5c6", "d7c6"]);
+        let deltas = analyzer.analyze(&Board::default(), &game);
+        for pair in deltas.windows(2) {
+            assert_eq!(pair[0].after, pair[1].before);
+        }
+        // A side's swings telescope: its eval after its last move's reply minus its first
+        let mut totals: Vec<i32> = deltas.iter().map(|d| d.before.total).collect();
+        totals.push(deltas.last().unwrap().after.total);
+        for side in 0..2 {
+            let own: Vec<&MoveDelta> = deltas.iter().skip(side).step_by(2).collect();
+            let last = own.last().unwrap();
+            let end = totals.get(last.ply + 2).copied().unwrap_or(-last.after.total);
+            let total: i32 = own.iter().map(|d| d.swing).sum();
+            assert_eq!(total, end - own[0].before.total);
+        }
+    }
+
+    #[test]
+    fn analysis_stops_at_an_illegal_move() {
+        let mut analyzer = Analyzer::new(GeotensorEvaluator::new(None));
+        let deltas = analyzer.analyze(&Board::default(), &moves(&["e2e4", "e2e4", "e7e5"]));
+        assert_eq!(deltas.len(), 1);
+        assert!(largest_negative_swing(&[]).is_none());
+    }
+}
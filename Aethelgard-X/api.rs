@@ -0,0 +1,73 @@
+use cozy_chess::*;
+use crate::error::GeoLlamaError;
+use crate::eval::GeotensorEvaluator;
+use crate::shadow::ShadowGuard;
+
+/// Parses a FEN (standard or Shredder) into a board.
+pub fn parse_fen(fen: &str) -> Result<Board, GeoLlamaError> {
+    Ok(fen.trim().parse::<Board>()?)
+}
+
+/// Static Geotensor evaluation of a FEN, from the side to move's perspective.
+pub fn eval_position(fen: &str) -> Result<i32, GeoLlamaError> {
+    let board = parse_fen(fen)?;
+    Ok(GeotensorEvaluator::new(None).evaluate(&board))
+}
+
+/// Best move found by a fixed-depth ShadowGuard search of a FEN.
+pub fn best_move(fen: &str, depth: i32) -> Result<Move, GeoLlamaError> {
+    let board = parse_fen(fen)?;
+    if !board.generate_moves(|_| true) {
+        return Err(GeoLlamaError::NoLegalMoves(board.status()));
+    }
+    let mut shadow = ShadowGuard::new();
+    let (_, mv) = shadow.search_with_move(&board, depth.max(1), -30000, 30000);
+    mv.ok_or(GeoLlamaError::NoLegalMoves(board.status()))
+}
+
+/// Parses and plays UCI moves in order, stopping at the first one that is
+/// malformed or illegal; the moves before it stay played.
+pub fn play_moves(board: &mut Board, moves: &[&str]) -> Result<(), GeoLlamaError> {
+    for &text in moves {
+        let mv: Move = text.parse().map_err(|_| GeoLlamaError::InvalidMove(text.to_string()))?;
+        board.try_play(mv).map_err(|_| GeoLlamaError::IllegalMove(mv))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fen_round_trips() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        ] {
+            assert_eq!(parse_fen(fen).unwrap().to_string(), fen);
+        }
+        assert!(matches!(parse_fen("not a fen"), Err(GeoLlamaError::InvalidFen(_))));
+    }
+
+    #[test]
+    fn entry_points_report_errors() {
+        assert!(eval_position("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").is_ok());
+        assert!(matches!(eval_position("8/8/8 w - - 0 1"), Err(GeoLlamaError::InvalidFen(_))));
+        // Fool's mate: White is mated
+        let mated = "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3";
+        assert!(matches!(best_move(mated, 2), Err(GeoLlamaError::NoLegalMoves(GameStatus::Won))));
+        let mv = best_move("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", 2).unwrap();
+        assert_eq!(mv.to_string(), "a1a8");
+    }
+
+    #[test]
+    fn play_moves_keeps_the_moves_before_an_error() {
+        let mut board = Board::default();
+        assert!(matches!(play_moves(&mut board, &["e2e4", "e7e5", "e4e5"]), Err(GeoLlamaError::IllegalMove(_))));
+        assert_eq!(board.to_string(), "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2");
+        assert!(matches!(play_moves(&mut board, &["g1f3", "e9"]), Err(GeoLlamaError::InvalidMove(_))));
+        assert_eq!(board.side_to_move(), Color::Black);
+    }
+}
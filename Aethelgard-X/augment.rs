@@ -0,0 +1,126 @@
+use cozy_chess::*;
+
+/// Symmetry applied to a training position by `augment`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Transform {
+    /// a-file <-> h-file
+    FileMirror,
+    /// Ranks mirrored and colors swapped, side to move included
+    ColorFlip,
+    FileMirrorColorFlip,
+}
+
+impl Transform {
+    pub fn flips_color(self) -> bool {
+        self != Transform::FileMirror
+    }
+
+    pub fn mirrors_file(self) -> bool {
+        self != Transform::ColorFlip
+    }
+
+    /// Image of a square; every transform is its own inverse, so this also maps back.
+    pub fn square(self, sq: Square) -> Square {
+        let sq = if self.mirrors_file() { sq.flip_file() } else { sq };
+        if self.flips_color() { sq.flip_rank() } else { sq }
+    }
+
+    /// Maps a move found in the transformed position back to the original.
+    pub fn move_back(self, mv: Move) -> Move {
+        Move { from: self.square(mv.from), to: self.square(mv.to), promotion: mv.promotion }
+    }
+
+    /// Maps a White-relative score back. Side-to-move-relative scores need no mapping.
+    pub fn score_back(self, white_score: i32) -> i32 {
+        if self.flips_color() { -white_score } else { white_score }
+    }
+}
+
+/// Symmetric variants of a training position (the original is not included).
+/// The color flip is exact; file mirrors are only produced without castling
+/// rights, since castling is not symmetric between the wings.
+pub fn augment(board: &Board) -> Vec<(Board, Transform)> {
+    let castling = Color::ALL.iter().any(|&c| {
+        let rights = board.castle_rights(c);
+        rights.short.is_some() || rights.long.is_some()
+    });
+    let transforms: &[Transform] = if castling {
+        &[Transform::ColorFlip]
+    } else {
+        &[Transform::FileMirror, Transform::ColorFlip, Transform::FileMirrorColorFlip]
+    };
+
+    transforms
+        .iter()
+        .filter_map(|&t| apply(board, t).map(|b| (b, t)))
+        .collect()
+}
+
+fn apply(board: &Board, t: Transform) -> Option<Board> {
+    let src = BoardBuilder::from_board(board);
+    let mut dst = src.clone();
+    for sq in Square::ALL {
+        *dst.square_mut(t.square(sq)) = src
+            .square(sq)
+            .map(|(piece, color)| (piece, if t.flips_color() { !color } else { color }));
+    }
+    dst.en_passant = src.en_passant.map(|sq| t.square(sq));
+    if t.flips_color() {
+        dst.side_to_move = !src.side_to_move;
+        dst.castle_rights = [src.castle_rights[1], src.castle_rights[0]];
+    }
+    dst.build().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bench::{legal_moves, random_positions};
+    use crate::eval::GeotensorEvaluator;
+
+    fn white_relative(board: &Board, score: i32) -> i32 {
+        if board.side_to_move() == Color::White { score } else { -score }
+    }
+
+    #[test]
+    fn start_position_only_flips_color() {
+        let start = Board::default();
+        let variants = augment(&start);
+        assert_eq!(variants.len(), 1);
+        let (flipped, t) = &variants[0];
+        assert_eq!(*t, Transform::ColorFlip);
+        // The flip maps the start array onto itself, with Black to move
+        assert_eq!(flipped.to_string(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1");
+    }
+
+    #[test]
+    fn variants_are_equivalent_positions() {
+        let mut evaluator = GeotensorEvaluator::new(None);
+        for board in random_positions(139, 100, 40) {
+            let mut builder = BoardBuilder::from_board(&board);
+            builder.castle_rights = [CastleRights::EMPTY; 2];
+            let board = builder.build().unwrap();
+            let variants = augment(&board);
+            assert_eq!(variants.len(), 3);
+
+            let moves = legal_moves(&board);
+            let original = evaluator.breakdown(&board);
+            for (variant, t) in variants {
+                // Every transform is its own inverse
+                let back = augment(&variant).into_iter().find(|&(_, u)| u == t).unwrap().0;
+                assert_eq!(back.to_string(), board.to_string());
+
+                let variant_moves = legal_moves(&variant);
+                assert_eq!(variant_moves.len(), moves.len());
+                assert!(variant_moves.iter().all(|&mv| moves.contains(&t.move_back(mv))));
+
+                // Side-to-move relative terms need no mapping back; White-relative ones do
+                let parts = evaluator.breakdown(&variant);
+                assert_eq!(parts.material, original.material);
+                assert!((parts.vision - original.vision).abs() < 1e-3, "{} vs {}", board, variant);
+                let material = parts.material as i32;
+                assert_eq!(t.score_back(white_relative(&variant, material)), white_relative(&board, material));
+            }
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use cozy_chess::*;
+use crate::rng::{RngSource, XorShift64};
+#[cfg(test)]
+use crate::cga::Multivector5D;
+
+/// Reproducible benchmark and tuning inputs: `count` positions, each reached
+/// from the start position by a uniformly random number (0..=`max_plies`) of
+/// uniformly random legal moves from one xorshift stream seeded by `seed`.
+/// A game that ends early stops there, so a position can be terminal; check
+/// `Board::status` where that matters.
+pub fn random_positions(seed: u64, count: usize, max_plies: usize) -> Vec<Board> {
+    random_positions_from(&mut XorShift64::new(seed), count, max_plies)
+}
+
+/// `random_positions` drawing from `rng` instead of a seeded xorshift.
+pub fn random_positions_from(rng: &mut (impl RngSource + ?Sized), count: usize, max_plies: usize) -> Vec<Board> {
+    let mut positions = Vec::with_capacity(count);
+    for _ in 0..count {
+        let mut board = Board::default();
+        let plies = (rng.next_u64() % (max_plies as u64 + 1)) as usize;
+        for _ in 0..plies {
+            let mut moves = Vec::new();
+            board.generate_moves(|mvs| {
+                moves.extend(mvs);
+                false
+            });
+            if moves.is_empty() {
+                break;
+            }
+            board.play(moves[(rng.next_u64() % moves.len() as u64) as usize]);
+        }
+        positions.push(board);
+    }
+    positions
+}
+
+/// Legal moves of `board` in generation order.
+#[cfg(test)]
+pub fn legal_moves(board: &Board) -> Vec<Move> {
+    let mut moves = Vec::new();
+    board.generate_moves(|mvs| {
+        moves.extend(mvs);
+        false
+    });
+    moves
+}
+
+/// Multivector with every lane uniform in [-1, 1).
+#[cfg(test)]
+pub fn random_multivector(rng: &mut XorShift64) -> Multivector5D {
+    let mut m = Multivector5D::zero();
+    for lane in m.lanes.iter_mut() {
+        *lane = rng.next_f32() * 2.0 - 1.0;
+    }
+    m
+}
+
+/// Times `iters` calls of `f` and prints the mean, summing the results into a
+/// checksum so the work cannot be optimized away. For `#[ignore]`d benchmarks.
+#[cfg(test)]
+pub fn bench(label: &str, iters: u32, mut f: impl FnMut() -> f32) {
+    let mut sink = 0.0;
+    let start = std::time::Instant::now();
+    for _ in 0..iters {
+        sink += std::hint::black_box(f());
+    }
+    println!("{}: {:?} per call (checksum {})", label, start.elapsed() / iters, sink);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeds_reproduce_their_positions() {
+        let fens = |seed| random_positions(seed, 20, 40).iter().map(|b| b.to_string()).collect::<Vec<_>>();
+        assert_eq!(fens(174), fens(174));
+        assert_ne!(fens(174), fens(175));
+        assert_eq!(random_positions_from(&mut XorShift64::new(174), 20, 40), random_positions(174, 20, 40));
+        // No plies at all leaves the start position
+        assert!(random_positions(174, 5, 0).iter().all(|b| *b == Board::default()));
+    }
+}
@@ -1,22 +1,59 @@
 use std::ops::{Add, Sub, Mul};
+use once_cell::sync::OnceCell;
 
-/// Aethelgard-X Multivector (32-float components for 5D CGA Cl(4,1))
-/// The basis is ordered as:
-/// 0: 1 (Scalar)
-/// 1-5: e1, e2, e3, e+, e- (Vectors)
-/// 6-15: Bivectors (e12, e13, e1+, e1-, e23, e2+, e2-, e3+, e3-, e+-)
-/// 16-25: Trivectors (e123, e12+, e12-, e13+, e13-, e1+-, e23+, e23-, e2+-, e3+-)
-/// 26-30: Quadvectors (e123+, e123-, e12+-, e13+-, e23+-)
-/// 31: e123+- (Pseudoscalar)
+/// Grade of each lane. Lanes are indexed by basis bitmask (bit n = e_{n+1}),
+/// so the grade is the popcount of the index.
+pub static GRADE: [u32; 32] = {
+    let mut grades = [0u32; 32];
+    let mut i = 0;
+    while i < 32 {
+        grades[i] = (i as u32).count_ones();
+        i += 1;
+    }
+    grades
+};
+
+/// Grade of lane `i`: `GRADE` for the lanes of Cl(4,1) and below, the
+/// popcount it stores for the larger experimental algebras.
+#[inline(always)]
+fn grade(i: usize) -> u32 {
+    if i < 32 { GRADE[i] } else { (i as u32).count_ones() }
+}
+
+/// Lane of the e+ basis vector
+pub const E_PLUS: usize = 8;
+/// Lane of the e- basis vector
+pub const E_MINUS: usize = 16;
+
+/// Multivector of Cl(n-1, 1) with N = 2^n lanes: the first n-1 basis vectors
+/// square to +1 and the last to -1. Lanes are indexed by basis bitmask (bit i =
+/// e_{i+1}), so the grade of lane i is popcount(i).
+///
+/// N = 32 is the engine's 5D CGA Cl(4,1) (`Multivector5D`); smaller or larger
+/// algebras such as Cl(3,1) (N = 16) or Cl(5,1) (N = 64) are for experiments.
 #[repr(C, align(64))]
 #[derive(Clone, Copy, Debug)]
-pub struct Multivector5D {
-    pub lanes: [f32; 32],
+pub struct Multivector<const N: usize> {
+    pub lanes: [f32; N],
 }
 
-impl Multivector5D {
+/// Aethelgard-X Multivector (32-float components for 5D CGA Cl(4,1))
+/// Lanes are indexed by basis bitmask: bit 0..4 = e1, e2, e3, e+, e-.
+/// 0: 1 (Scalar)
+/// 1, 2, 4, 8, 16: e1, e2, e3, e+, e- (Vectors)
+/// 3: e12, 5: e13, 6: e23, 9: e1+, 17: e1-, 24: e+-, ... (Bivectors)
+/// 31: e123+- (Pseudoscalar)
+pub type Multivector5D = Multivector<32>;
+
+impl<const N: usize> Multivector<N> {
+    /// Number of basis vectors; N must be a power of two with at least one.
+    pub const DIM: usize = {
+        assert!(N.is_power_of_two() && N >= 2, "lane count must be 2^n with n >= 1");
+        N.trailing_zeros() as usize
+    };
+
     pub fn zero() -> Self {
-        Self { lanes: [0.0; 32] }
+        Self { lanes: [0.0; N] }
     }
 
     pub fn new_scalar(s: f32) -> Self {
@@ -25,74 +62,115 @@ impl Multivector5D {
         m
     }
 
-    /// Basis vectors
+    /// Basis vector e_i for i = 1..=DIM (lane 1 << (i - 1)); e1, e2, e3, e+, e- in Cl(4,1)
     pub fn e(i: usize) -> Self {
         let mut m = Self::zero();
-        if i >= 1 && i <= 5 {
-            m.lanes[i] = 1.0;
+        if (1..=Self::DIM).contains(&i) {
+            m.lanes[1 << (i - 1)] = 1.0;
         }
         m
     }
 
-    /// Conformal Null Basis: n_infinity = e- + e+
-    pub fn n_inf() -> Self {
-        let mut m = Self::zero();
-        m.lanes[5] = 1.0; // e-
-        m.lanes[4] = 1.0; // e+
-        m
-    }
-
-    /// Conformal Null Basis: n_o = 0.5 * (e- - e+)
-    pub fn n_o() -> Self {
-        let mut m = Self::zero();
-        m.lanes[5] = 0.5;  // e-
-        m.lanes[4] = -0.5; // e+
-        m
-    }
-
-    /// Maps a board coordinate (x, y) to a Conformal Point
-    /// P = n_o + x*e1 + y*e2 + 0.5*(x^2 + y^2)*n_inf
-    pub fn point(x: f32, y: f32) -> Self {
-        let e1 = Self::e(1);
-        let e2 = Self::e(2);
-        let no = Self::n_o();
-        let ninf = Self::n_inf();
-        
-        no + (e1 * x) + (e2 * y) + (ninf * (0.5 * (x * x + y * y)))
+    /// Bit-exact hash key for memoization (f32 is neither Eq nor Hash).
+    /// -0.0 is folded onto +0.0 and every NaN onto one canonical NaN.
+    pub fn quantized_key(&self) -> [u32; N] {
+        let mut key = [0u32; N];
+        for (k, &v) in key.iter_mut().zip(self.lanes.iter()) {
+            *k = if v.is_nan() {
+                f32::NAN.to_bits()
+            } else if v == 0.0 {
+                0
+            } else {
+                v.to_bits()
+            };
+        }
+        key
     }
 
+    /// Scalar and vector part of the inner product, under the algebra's metric
     #[inline(always)]
     pub fn inner_product(&self, other: &Self) -> f32 {
-        // Metric for Cl(4,1): e1..e3=+1, e+=+1, e-=-1
-        // Using the M4's ability to fuse multiply-adds
         let mut dot = self.lanes[0] * other.lanes[0]; // Scalar
-        dot += self.lanes[1] * other.lanes[1]; // e1
-        dot += self.lanes[2] * other.lanes[2]; // e2
-        dot += self.lanes[3] * other.lanes[3]; // e3
-        dot += self.lanes[4] * other.lanes[4]; // e+
-        dot -= self.lanes[5] * other.lanes[5]; // e- (Minkowski)
+        for i in 0..Self::DIM {
+            let lane = 1 << i;
+            let term = self.lanes[lane] * other.lanes[lane];
+            // Only the last basis vector (e- in Cl(4,1)) is Minkowski
+            if i + 1 == Self::DIM { dot -= term } else { dot += term }
+        }
         dot
     }
 
     /// Reverse operator
     pub fn reverse(&self) -> Self {
         let mut res = *self;
-        for i in 0..32 {
-            // Number of basis elements k in the blade
-            let k = (i as u32).count_ones();
-            if k * (k - 1) / 2 % 2 == 1 {
+        for i in 0..N {
+            // Number of basis elements k in the blade. Reversing it takes
+            // k(k-1)/2 swaps, odd exactly for grades 2 and 3 mod 4; written
+            // this way the scalar (k = 0) can't underflow
+            let k = grade(i);
+            if k % 4 >= 2 {
                 res.lanes[i] *= -1.0;
             }
         }
         res
     }
 
-    /// Translator Versor: T = 1 - 0.5 * (dx*e1 + dy*e2) * n_inf
-    pub fn translator(dx: f32, dy: f32) -> Self {
-        let one = Self::new_scalar(1.0);
-        let d = Self::e(1) * dx + Self::e(2) * dy;
-        let ninf = Self::n_inf();
-        one - (d * ninf) * 0.5
+    /// Grade involution: negates the odd-grade lanes
+    pub fn involution(&self) -> Self {
+        let mut res = *self;
+        for i in 0..N {
+            if grade(i) % 2 == 1 {
+                res.lanes[i] = -res.lanes[i];
+            }
+        }
+        res
+    }
+
+    /// Grade projection <M>_g
+    pub fn grade_part(&self, g: u32) -> Self {
+        let mut res = Self::zero();
+        for i in 0..N {
+            if grade(i) == g {
+                res.lanes[i] = self.lanes[i];
+            }
+        }
+        res
+    }
+
+    /// `out = self + other`, written into a caller-owned buffer (no zeroing or
+    /// returned temporary in hot loops).
+    #[inline(always)]
+    pub fn add_into(&self, other: &Self, out: &mut Self) {
+        for ((o, a), b) in out.lanes.iter_mut().zip(self.lanes.iter()).zip(other.lanes.iter()) {
+            *o = a + b;
+        }
+    }
+
+    /// `out = self * s`, written into a caller-owned buffer.
+    #[inline(always)]
+    pub fn scale_into(&self, s: f32, out: &mut Self) {
+        for (o, a) in out.lanes.iter_mut().zip(self.lanes.iter()) {
+            *o = a * s;
+        }
+    }
+
+    /// `self += other` in place.
+    #[inline(always)]
+    pub fn add_assign_lanes(&mut self, other: &Self) {
+        for (a, b) in self.lanes.iter_mut().zip(other.lanes.iter()) {
+            *a += b;
+        }
+    }
+
+    /// Versor inverse: V^-1 = V_rev / (V * V_rev). Only valid for versors (products
+    /// of invertible vectors), where V * V_rev is a scalar; None if that scalar is ~0.
+    pub fn inverse(&self) -> Option<Self> {
+        let rev = self.reverse();
+        let norm = (*self * rev).lanes[0];
+        if norm.abs() < 1e-6 {
+            return None;
+        }
+        Some(rev * (1.0 / norm))
     }
 
     /// Applies a versor transformation: M' = V * M * V_rev
@@ -105,47 +183,160 @@ impl Multivector5D {
     /// significantly improving cache locality and throughput.
     #[inline(always)]
     pub fn geometric_product(&self, other: &Self) -> Self {
-        let mut lanes = [0.0; 32];
-        let table = &crate::geometry_tables::GP_MAP;
+        let mut lanes = [0.0; N];
+        let table = Self::gp_table();
         
         // The table is ordered by output coefficient 'k'.
-        // For each k, there are exactly 32 contributing pairs (sum of products).
-        // Total 1024 ops, but linear memory access.
+        // For each k, there are exactly N contributing pairs (sum of products).
+        // N^2 ops in total, but linear memory access.
         let mut idx = 0;
-        for k in 0..32 {
+        for lane in lanes.iter_mut() {
             let mut acc = 0.0;
-            // Unrolling 32 times for auto-vectorization
-            for _ in 0..32 {
+            // Fixed trip count for auto-vectorization
+            for _ in 0..N {
                 let (sign, a, b) = table[idx];
                 acc += sign * self.lanes[a] * other.lanes[b];
                 idx += 1;
             }
-            lanes[k] = acc;
+            *lane = acc;
         }
         Self { lanes }
     }
 
+    /// `out[n] = self * others[n]` for a whole batch. Walks the GP map once with the
+    /// left lanes hoisted, skipping terms where `self` is zero, and runs the inner
+    /// loop over the batch. Matches `geometric_product` element-wise.
+    pub fn geometric_product_batch(&self, others: &[Self], out: &mut [Self]) {
+        assert_eq!(others.len(), out.len(), "batch input and output lengths differ");
+        for o in out.iter_mut() {
+            *o = Self::zero();
+        }
+        let table = Self::gp_table();
+        for (idx, &(sign, a, b)) in table.iter().enumerate() {
+            let coef = sign * self.lanes[a];
+            if coef == 0.0 { continue; }
+            let k = idx / N;
+            for (o, m) in out.iter_mut().zip(others) {
+                o.lanes[k] += coef * m.lanes[b];
+            }
+        }
+    }
+
+    /// GP map of this algebra: the `GP_MAP` static for Cl(4,1), so the engine's
+    /// products skip the per-dimension cache; `gp_map` for the others.
+    #[inline(always)]
+    fn gp_table() -> &'static [(f32, usize, usize)] {
+        if N == 32 { &crate::geometry_tables::GP_MAP } else { crate::geometry_tables::gp_map(Self::DIM) }
+    }
+
+    /// Cayley table of this algebra, flattened; `CAYLEY_TABLE` for Cl(4,1).
+    #[inline(always)]
+    fn cayley() -> &'static [(f32, usize)] {
+        if N == 32 { CAYLEY_TABLE.as_flattened() } else { cayley_table(Self::DIM) }
+    }
+
     /// Outer Product (Wedge): A ^ B
     pub fn wedge(&self, other: &Self) -> Self {
         let mut res = Self::zero();
+        let table = Self::cayley();
+
+        // Gather the nonzero lanes of B once instead of rescanning per lane of A
+        let mut b_idx = [0usize; N];
+        let mut b_len = 0;
+        for (j, &b_val) in other.lanes.iter().enumerate() {
+            if b_val != 0.0 {
+                b_idx[b_len] = j;
+                b_len += 1;
+            }
+        }
+
         for (i, &a_val) in self.lanes.iter().enumerate() {
             if a_val == 0.0 { continue; }
-            let grade_a = (i as u32).count_ones();
-            let table_row = &CAYLEY_TABLE[i];
-            for (j, &b_val) in other.lanes.iter().enumerate() {
-                if b_val == 0.0 { continue; }
-                let grade_b = (j as u32).count_ones();
+            let table_row = &table[i * N..(i + 1) * N];
+            for &j in &b_idx[..b_len] {
+                // Blades sharing a basis vector drop grade in the product, so only
+                // disjoint masks reach grade_a + grade_b
+                if i & j != 0 { continue; }
                 let (sign, k) = table_row[j];
-                let grade_res = (k as u32).count_ones();
-                
-                if grade_res == (grade_a + grade_b) {
-                    res.lanes[k] += sign * a_val * b_val;
-                }
+                res.lanes[k] += sign * a_val * other.lanes[j];
+            }
+        }
+        res
+    }
+
+    /// Reference for `wedge` built on the geometric product instead of mask
+    /// filtering: the sum over grade pairs of <<A>_r <B>_s>_(r+s). Slow, but a
+    /// Cayley sign error in only one of the two shows up when comparing them.
+    #[cfg(test)]
+    pub fn wedge_from_product(&self, other: &Self) -> Self {
+        let mut res = Self::zero();
+        for r in 0..=Self::DIM as u32 {
+            let a = self.grade_part(r);
+            if a.lanes.iter().all(|&v| v == 0.0) { continue; }
+            for s in 0..=Self::DIM as u32 - r {
+                let b = other.grade_part(s);
+                res.add_assign_lanes(&a.geometric_product(&b).grade_part(r + s));
             }
         }
         res
     }
 
+    /// Incidence ("meet") test: a point lies on a flat blade iff P ^ B = 0.
+    pub fn is_incident(&self, point: &Self) -> bool {
+        point.wedge(self).lanes.iter().all(|v| v.abs() < 1e-3)
+    }
+
+    /// Largest absolute lane, used to scale the blade tests below.
+    fn magnitude(&self) -> f32 {
+        self.lanes.iter().fold(0.0, |m, v| m.max(v.abs()))
+    }
+}
+
+impl Multivector5D {
+    /// Conformal Null Basis: n_infinity = e- + e+
+    pub fn n_inf() -> Self {
+        let mut m = Self::zero();
+        m.lanes[E_MINUS] = 1.0;
+        m.lanes[E_PLUS] = 1.0;
+        m
+    }
+
+    /// Conformal Null Basis: n_o = 0.5 * (e- - e+)
+    pub fn n_o() -> Self {
+        let mut m = Self::zero();
+        m.lanes[E_MINUS] = 0.5;
+        m.lanes[E_PLUS] = -0.5;
+        m
+    }
+
+    /// Maps a board coordinate (x, y) to a Conformal Point
+    /// P = n_o + x*e1 + y*e2 + 0.5*(x^2 + y^2)*n_inf
+    pub fn point(x: f32, y: f32) -> Self {
+        let e1 = Self::e(1);
+        let e2 = Self::e(2);
+        let no = Self::n_o();
+        let ninf = Self::n_inf();
+        
+        no + (e1 * x) + (e2 * y) + (ninf * (0.5 * (x * x + y * y)))
+    }
+
+    /// Translator Versor: T = 1 - 0.5 * (dx*e1 + dy*e2) * n_inf
+    /// With this basis and metric, `point(x, y).transform(&translator(dx, dy))`
+    /// lands on `point(x + dx, y + dy)` (`to_euclidean` reads back `(x + dx, y + dy)`).
+    pub fn translator(dx: f32, dy: f32) -> Self {
+        let one = Self::new_scalar(1.0);
+        let d = Self::e(1) * dx + Self::e(2) * dy;
+        let ninf = Self::n_inf();
+        one - (d * ninf) * 0.5
+    }
+
+    /// Board coordinates (x, y) of a conformal point, undoing any scale the
+    /// point picked up (e.g. the sign flip from a reflection versor).
+    pub fn to_euclidean(self) -> (f32, f32) {
+        let w = -self.inner_product(&Self::n_inf());
+        (self.lanes[1] / w, self.lanes[2] / w)
+    }
+
     pub fn dual(&self) -> Self {
         let mut i_inv = Self::zero();
         i_inv.lanes[31] = -1.0; // Pseudoscalar inverse for Cl(4,1)
@@ -159,6 +350,26 @@ impl Multivector5D {
         p.wedge(&e1).wedge(&ninf)
     }
 
+    /// Line through P with direction dx*e1 + dy*e2: P ^ d ^ n_inf
+    pub fn line_blade(p: &Self, dx: f32, dy: f32) -> Self {
+        let d = Self::e(1) * dx + Self::e(2) * dy;
+        p.wedge(&d).wedge(&Self::n_inf())
+    }
+
+    /// True if n_inf lies in the blade (B ^ n_inf = 0), i.e. it passes through
+    /// infinity. Holds for lines/planes and n_inf itself, never for a finite point.
+    pub fn contains_infinity(&self) -> bool {
+        let mag = self.magnitude();
+        mag > 0.0 && self.wedge(&Self::n_inf()).magnitude() < 1e-4 * mag
+    }
+
+    /// Flat (line, plane, flat point) rather than round (point, circle, sphere):
+    /// contains infinity and has no scalar or vector part.
+    pub fn is_flat(&self) -> bool {
+        let mag = self.magnitude();
+        self.contains_infinity() && (0..32).filter(|&i| GRADE[i] < 2).all(|i| self.lanes[i].abs() < 1e-4 * mag)
+    }
+
     /// The Bishop Plane: P ^ (e1+e2) ^ n_inf
     pub fn bishop_blade(p: &Self) -> Self {
         let diag = Self::e(1) + Self::e(2);
@@ -167,29 +378,109 @@ impl Multivector5D {
     }
 }
 
+/// The eight symmetries of the board square: rotations about the center and
+/// reflections in its four axes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BoardSymmetry {
+    Identity,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    /// x -> 7 - x
+    MirrorFile,
+    /// y -> 7 - y
+    MirrorRank,
+    /// (x, y) -> (y, x)
+    Diagonal,
+    /// (x, y) -> (7 - y, 7 - x)
+    AntiDiagonal,
+}
+
+impl BoardSymmetry {
+    pub const ALL: [BoardSymmetry; 8] = [
+        BoardSymmetry::Identity,
+        BoardSymmetry::Rotate90,
+        BoardSymmetry::Rotate180,
+        BoardSymmetry::Rotate270,
+        BoardSymmetry::MirrorFile,
+        BoardSymmetry::MirrorRank,
+        BoardSymmetry::Diagonal,
+        BoardSymmetry::AntiDiagonal,
+    ];
+}
+
+/// Versor applying `sym` to `BOARD_SPACE` points via `transform`: the rotor or
+/// reflecting normal at the origin, conjugated by translators to the board
+/// center (3.5, 3.5). Reflections are odd versors and negate the point, which
+/// `to_euclidean` normalizes away.
+pub fn board_symmetry_versor(sym: BoardSymmetry) -> Multivector5D {
+    // Rotor cos(θ/2) - sin(θ/2) e12, turning e1 towards e2 by θ
+    let rotor = |half_angle: f32| {
+        let mut r = Multivector5D::new_scalar(half_angle.cos());
+        r.lanes[3] = -half_angle.sin();
+        r
+    };
+    let frac = std::f32::consts::FRAC_1_SQRT_2;
+    let core = match sym {
+        BoardSymmetry::Identity => Multivector5D::new_scalar(1.0),
+        BoardSymmetry::Rotate90 => rotor(std::f32::consts::FRAC_PI_4),
+        BoardSymmetry::Rotate180 => rotor(std::f32::consts::FRAC_PI_2),
+        BoardSymmetry::Rotate270 => rotor(3.0 * std::f32::consts::FRAC_PI_4),
+        BoardSymmetry::MirrorFile => Multivector5D::e(1),
+        BoardSymmetry::MirrorRank => Multivector5D::e(2),
+        BoardSymmetry::Diagonal => (Multivector5D::e(1) - Multivector5D::e(2)) * frac,
+        BoardSymmetry::AntiDiagonal => (Multivector5D::e(1) + Multivector5D::e(2)) * frac,
+    };
+    Multivector5D::translator(3.5, 3.5) * core * Multivector5D::translator(-3.5, -3.5)
+}
+
+/// Largest algebra with generated tables: Cl(6,1), 128 lanes
+pub const MAX_DIM: usize = 7;
+
 /// Precomputed Cayley Table for Cl(4,1)
 pub static CAYLEY_TABLE: once_cell::sync::Lazy<[[(f32, usize); 32]; 32]> = once_cell::sync::Lazy::new(|| {
     let mut table = [[(0.0, 0); 32]; 32];
-    for i in 0..32 {
-        for j in 0..32 {
-            table[i][j] = basis_product_logic(i, j);
+    for (i, row) in table.iter_mut().enumerate() {
+        for (j, entry) in row.iter_mut().enumerate() {
+            *entry = basis_product_logic(i, j, 5);
         }
     }
     table
 });
 
-fn basis_product_logic(a: usize, b: usize) -> (f32, usize) {
+/// Cayley table of Cl(dim-1, 1), flattened row-major: entry a * 2^dim + b is
+/// (sign, lane) of basis(a) * basis(b). `CAYLEY_TABLE` for Cl(4,1); the other
+/// algebras' tables are generated once on first use.
+pub fn cayley_table(dim: usize) -> &'static [(f32, usize)] {
+    static TABLES: [OnceCell<Vec<(f32, usize)>>; MAX_DIM + 1] = [const { OnceCell::new() }; MAX_DIM + 1];
+    if dim == 5 {
+        return CAYLEY_TABLE.as_flattened();
+    }
+    assert!(dim <= MAX_DIM, "no Cayley table for a {}-dimensional algebra", dim);
+    TABLES[dim].get_or_init(|| {
+        let n = 1 << dim;
+        let mut table = Vec::with_capacity(n * n);
+        for i in 0..n {
+            for j in 0..n {
+                table.push(basis_product_logic(i, j, dim));
+            }
+        }
+        table
+    })
+}
+
+fn basis_product_logic(a: usize, b: usize, dim: usize) -> (f32, usize) {
     let mut sign = 1.0;
     let mut a_bits = a;
-    for i in 0..5 {
+    for i in 0..dim {
         if (b >> i) & 1 == 1 {
-            for j in (i + 1)..5 {
+            for j in (i + 1)..dim {
                 if (a_bits >> j) & 1 == 1 {
                     sign *= -1.0;
                 }
             }
             if (a_bits >> i) & 1 == 1 {
-                if i == 4 { // e5 (e-) metric is -1
+                if i == dim - 1 { // last basis vector (e- in Cl(4,1)) has metric -1
                     sign *= -1.0;
                 }
                 a_bits &= !(1 << i);
@@ -201,46 +492,120 @@ fn basis_product_logic(a: usize, b: usize) -> (f32, usize) {
     (sign, a_bits)
 }
 
-impl Mul for Multivector5D {
+impl<const N: usize> Mul for Multivector<N> {
     type Output = Self;
     fn mul(self, other: Self) -> Self {
         self.geometric_product(&other)
     }
 }
 
-impl Add for Multivector5D {
+impl<const N: usize> Add for Multivector<N> {
     type Output = Self;
     fn add(self, other: Self) -> Self {
-        let mut res = Self::zero();
-        for i in 0..32 {
-            res.lanes[i] = self.lanes[i] + other.lanes[i];
-        }
+        let mut res = self;
+        res.add_assign_lanes(&other);
         res
     }
 }
 
-impl Sub for Multivector5D {
+impl<const N: usize> Sub for Multivector<N> {
     type Output = Self;
     fn sub(self, other: Self) -> Self {
         let mut res = Self::zero();
-        for i in 0..32 {
+        for i in 0..N {
             res.lanes[i] = self.lanes[i] - other.lanes[i];
         }
         res
     }
 }
 
-impl Mul<f32> for Multivector5D {
+impl<const N: usize> Mul<f32> for Multivector<N> {
     type Output = Self;
     fn mul(self, s: f32) -> Self {
-        let mut res = Self::zero();
-        for i in 0..32 {
-            res.lanes[i] = self.lanes[i] * s;
+        let mut res = self;
+        for v in res.lanes.iter_mut() {
+            *v *= s;
         }
         res
     }
 }
 
+/// Mostly-zero multivector stored as its nonzero (lane, value) pairs. Points
+/// touch 4 lanes and line blades a handful, so incidence tests against them
+/// skip the ~28 zero lanes a dense product would walk.
+#[derive(Clone, Debug)]
+pub struct SparseBlade {
+    lanes: [u8; 32],
+    values: [f32; 32],
+    len: usize,
+}
+
+impl SparseBlade {
+    pub fn from_dense(m: &Multivector5D) -> Self {
+        let mut sparse = Self { lanes: [0; 32], values: [0.0; 32], len: 0 };
+        for (i, &v) in m.lanes.iter().enumerate() {
+            if v != 0.0 {
+                sparse.lanes[sparse.len] = i as u8;
+                sparse.values[sparse.len] = v;
+                sparse.len += 1;
+            }
+        }
+        sparse
+    }
+
+    pub fn to_dense(&self) -> Multivector5D {
+        let mut m = Multivector5D::zero();
+        for (lane, value) in self.iter() {
+            m.lanes[lane] = value;
+        }
+        m
+    }
+
+    /// Nonzero (lane, value) pairs in lane order.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, f32)> + '_ {
+        self.lanes[..self.len].iter().zip(&self.values[..self.len]).map(|(&l, &v)| (l as usize, v))
+    }
+
+    /// Same result as `Multivector5D::inner_product` on the dense form.
+    #[inline(always)]
+    pub fn inner_product(&self, other: &Multivector5D) -> f32 {
+        let mut dot = 0.0;
+        for (lane, value) in self.iter() {
+            match lane {
+                0 | 1 | 2 | 4 | E_PLUS => dot += value * other.lanes[lane],
+                E_MINUS => dot -= value * other.lanes[lane],
+                _ => {}
+            }
+        }
+        dot
+    }
+
+    /// Sparse `a ^ b`: only pairs of nonzero lanes with disjoint basis masks.
+    pub fn wedge(&self, other: &Self) -> Multivector5D {
+        let mut res = Multivector5D::zero();
+        let table = CAYLEY_TABLE.as_flattened();
+        for (i, a) in self.iter() {
+            let table_row = &table[i * 32..(i + 1) * 32];
+            for (j, b) in other.iter() {
+                if i & j != 0 { continue; }
+                let (sign, k) = table_row[j];
+                res.lanes[k] += sign * a * b;
+            }
+        }
+        res
+    }
+
+    /// Same test as `Multivector5D::is_incident`: P ^ B = 0.
+    pub fn is_incident(&self, point: &Self) -> bool {
+        point.wedge(self).lanes.iter().all(|v| v.abs() < 1e-3)
+    }
+}
+
+/// Sparse copies of `BOARD_SPACE` for incidence tests.
+pub static SPARSE_BOARD_SPACE: once_cell::sync::Lazy<[SparseBlade; 64]> = once_cell::sync::Lazy::new(|| {
+    std::array::from_fn(|sq| SparseBlade::from_dense(&BOARD_SPACE[sq]))
+});
+
 /// Lookup table for board points in CGA space
 pub static BOARD_SPACE: once_cell::sync::Lazy<[Multivector5D; 64]> = once_cell::sync::Lazy::new(|| {
     let mut table = [Multivector5D { lanes: [0.0; 32] }; 64];
@@ -251,3 +616,396 @@ pub static BOARD_SPACE: once_cell::sync::Lazy<[Multivector5D; 64]> = once_cell::
     }
     table
 });
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bench::{bench, random_multivector};
+    use crate::rng::{RngSource, XorShift64};
+
+    fn random_vector(rng: &mut XorShift64) -> Multivector5D {
+        random_multivector(rng).grade_part(1)
+    }
+
+    fn assert_close(a: &Multivector5D, b: &Multivector5D, tolerance: f32) {
+        for i in 0..32 {
+            assert!((a.lanes[i] - b.lanes[i]).abs() <= tolerance, "lane {}: {} vs {}", i, a.lanes[i], b.lanes[i]);
+        }
+    }
+
+    #[test]
+    fn grade_table_is_the_lane_popcount() {
+        for (i, &g) in GRADE.iter().enumerate() {
+            assert_eq!(g, (i as u32).count_ones(), "lane {}", i);
+            assert_eq!(grade(i), g);
+        }
+        assert_eq!(grade(127), 7);
+        // Projections onto every grade partition a multivector
+        let m = random_multivector(&mut XorShift64::new(115));
+        let sum = (0..=5).fold(Multivector5D::zero(), |acc, g| acc + m.grade_part(g));
+        assert_eq!(sum.lanes, m.lanes);
+    }
+
+    #[test]
+    fn reverse_signs_by_grade() {
+        let scalar = Multivector5D::new_scalar(1.0);
+        assert_eq!(scalar.reverse().lanes, scalar.lanes);
+        for grade in 0..=5u32 {
+            let mut m = Multivector5D::zero();
+            for (i, lane) in m.lanes.iter_mut().enumerate() {
+                if (i as u32).count_ones() == grade {
+                    *lane = 1.0 + i as f32;
+                }
+            }
+            // k(k-1)/2 swaps, computed signed so grade 0 can't underflow
+            let k = grade as i32;
+            let sign = if (k * (k - 1) / 2) % 2 == 0 { 1.0 } else { -1.0 };
+            let reversed = m.reverse();
+            for i in 0..32 {
+                assert_eq!(reversed.lanes[i], sign * m.lanes[i], "grade {} lane {}", grade, i);
+            }
+        }
+    }
+
+    #[test]
+    fn basis_vectors_sit_in_their_bitmask_lanes() {
+        for i in 1..=5 {
+            let e = Multivector5D::e(i);
+            let lane = 1 << (i - 1);
+            assert!(e.lanes.iter().enumerate().all(|(l, &v)| v == if l == lane { 1.0 } else { 0.0 }), "e{}", i);
+            assert_eq!(GRADE[lane], 1);
+        }
+        assert_eq!(Multivector5D::e(4).lanes[E_PLUS], 1.0);
+        assert_eq!(Multivector5D::e(5).lanes[E_MINUS], 1.0);
+    }
+
+    #[test]
+    fn conformal_basis_vectors_are_null() {
+        let (n_inf, n_o) = (Multivector5D::n_inf(), Multivector5D::n_o());
+        assert_close(&(n_inf * n_inf), &Multivector5D::zero(), 0.0);
+        assert_close(&(n_o * n_o), &Multivector5D::zero(), 0.0);
+        assert_eq!(n_inf.inner_product(&n_inf), 0.0);
+        assert_eq!(n_o.inner_product(&n_o), 0.0);
+        assert_eq!(n_inf.inner_product(&n_o), -1.0);
+        assert_eq!((n_inf * n_o + n_o * n_inf).lanes[0], -2.0);
+    }
+
+    #[test]
+    fn basis_vectors_square_to_the_metric() {
+        for i in 1..=5 {
+            let square = Multivector5D::e(i) * Multivector5D::e(i);
+            let metric = if i == 5 { -1.0 } else { 1.0 };
+            assert_close(&square, &Multivector5D::new_scalar(metric), 0.0);
+        }
+    }
+
+    /// Basis identities of Cl(DIM-1, 1): e_i^2 is the metric (-1 only for the
+    /// last basis vector), and for i < j, e_i e_j is the bivector lane with both
+    /// bits set while e_j e_i is its negative.
+    fn assert_basis_identities<const N: usize>() {
+        let dim = Multivector::<N>::DIM;
+        for i in 1..=dim {
+            let ei = Multivector::<N>::e(i);
+            let metric = if i == dim { -1.0 } else { 1.0 };
+            assert_eq!((ei * ei).lanes, Multivector::<N>::new_scalar(metric).lanes, "Cl({},1): e{}^2", dim - 1, i);
+            for j in (i + 1)..=dim {
+                let ej = Multivector::<N>::e(j);
+                let mut blade = Multivector::<N>::zero();
+                blade.lanes[(1 << (i - 1)) | (1 << (j - 1))] = 1.0;
+                assert_eq!((ei * ej).lanes, blade.lanes, "Cl({},1): e{} e{}", dim - 1, i, j);
+                assert_eq!((ej * ei).lanes, (blade * -1.0).lanes, "Cl({},1): e{} e{}", dim - 1, j, i);
+            }
+        }
+    }
+
+    #[test]
+    fn smaller_and_larger_algebras_satisfy_basis_identities() {
+        assert_basis_identities::<2>();
+        assert_basis_identities::<4>();
+        assert_basis_identities::<8>();
+        assert_basis_identities::<16>();
+        assert_basis_identities::<32>();
+        assert_basis_identities::<64>();
+        // Cl(2,1) products stay associative through its own generated tables
+        let mut rng = XorShift64::new(154);
+        let mut random = || {
+            let mut m = Multivector::<8>::zero();
+            for lane in m.lanes.iter_mut() {
+                *lane = rng.next_f32() * 2.0 - 1.0;
+            }
+            m
+        };
+        for _ in 0..20 {
+            let (a, b, c) = (random(), random(), random());
+            for (x, y) in ((a * b) * c).lanes.iter().zip(&(a * (b * c)).lanes) {
+                assert!((x - y).abs() < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn engine_tables_are_the_generated_cl41_tables() {
+        assert!(std::ptr::eq(cayley_table(5), CAYLEY_TABLE.as_flattened()));
+        assert!(std::ptr::eq(crate::geometry_tables::gp_map(5), &crate::geometry_tables::GP_MAP[..]));
+        for (i, row) in CAYLEY_TABLE.iter().enumerate() {
+            for (j, &entry) in row.iter().enumerate() {
+                assert_eq!(entry, basis_product_logic(i, j, 5));
+            }
+        }
+    }
+
+    #[test]
+    fn geometric_product_is_associative() {
+        let mut rng = XorShift64::new(130);
+        for _ in 0..50 {
+            let (a, b, c) = (random_multivector(&mut rng), random_multivector(&mut rng), random_multivector(&mut rng));
+            assert_close(&((a * b) * c), &(a * (b * c)), 1e-3);
+        }
+    }
+
+    #[test]
+    fn versor_times_inverse_is_one() {
+        let mut rng = XorShift64::new(131);
+        let mut versors: Vec<Multivector5D> = BoardSymmetry::ALL.iter().map(|&sym| board_symmetry_versor(sym)).collect();
+        versors.push(Multivector5D::translator(2.5, -1.0));
+        for _ in 0..20 {
+            // Products of up to three random vectors, each invertible unless null
+            let mut v = random_vector(&mut rng);
+            for _ in 0..(rng.next_u64() % 3) {
+                v = v * random_vector(&mut rng);
+            }
+            versors.push(v);
+        }
+        let one = Multivector5D::new_scalar(1.0);
+        for v in versors {
+            let Some(inv) = v.inverse() else { continue };
+            assert_close(&(v * inv), &one, 1e-3);
+            assert_close(&(inv * v), &one, 1e-3);
+        }
+        // A null vector has no inverse
+        assert!(Multivector5D::n_inf().inverse().is_none());
+    }
+
+    /// `to_euclidean` of `point(x, y)` moved by `versor`.
+    fn moved(x: f32, y: f32, versor: &Multivector5D) -> (f32, f32) {
+        Multivector5D::point(x, y).transform(versor).to_euclidean()
+    }
+
+    fn assert_at(actual: (f32, f32), expected: (f32, f32)) {
+        assert!((actual.0 - expected.0).abs() < 1e-4 && (actual.1 - expected.1).abs() < 1e-4, "{:?} vs {:?}", actual, expected);
+    }
+
+    #[test]
+    fn translator_moves_points_by_its_offset() {
+        assert_at(moved(2.0, 3.0, &Multivector5D::translator(1.0, -1.0)), (3.0, 2.0));
+        assert_at(moved(2.0, 3.0, &Multivector5D::translator(0.0, 0.0)), (2.0, 3.0));
+    }
+
+    #[test]
+    fn board_symmetries_map_the_corners() {
+        let at = |sym, x, y| moved(x, y, &board_symmetry_versor(sym));
+        assert_at(at(BoardSymmetry::Identity, 2.0, 5.0), (2.0, 5.0));
+        assert_at(at(BoardSymmetry::Rotate180, 0.0, 0.0), (7.0, 7.0));
+        assert_at(at(BoardSymmetry::Rotate180, 7.0, 0.0), (0.0, 7.0));
+        assert_at(at(BoardSymmetry::MirrorFile, 0.0, 0.0), (7.0, 0.0));
+        assert_at(at(BoardSymmetry::MirrorFile, 7.0, 7.0), (0.0, 7.0));
+        assert_at(at(BoardSymmetry::MirrorRank, 0.0, 0.0), (0.0, 7.0));
+        assert_at(at(BoardSymmetry::MirrorRank, 7.0, 7.0), (7.0, 0.0));
+        assert_at(at(BoardSymmetry::Diagonal, 7.0, 0.0), (0.0, 7.0));
+        assert_at(at(BoardSymmetry::AntiDiagonal, 0.0, 0.0), (7.0, 7.0));
+        // A quarter turn each way undoes the other
+        let there = at(BoardSymmetry::Rotate90, 1.0, 0.0);
+        assert_at(at(BoardSymmetry::Rotate270, there.0, there.1), (1.0, 0.0));
+    }
+
+    #[test]
+    fn wedge_matches_grade_projected_product() {
+        let mut rng = XorShift64::new(165);
+        for _ in 0..50 {
+            // Blades of grade 1 to 3 as wedges of random vectors
+            let blade = |rng: &mut XorShift64, grade: usize| {
+                (1..grade).fold(random_vector(rng), |b, _| b.wedge(&random_vector(rng)))
+            };
+            let grade_a = 1 + (rng.next_u64() % 3) as usize;
+            let grade_b = 1 + (rng.next_u64() % 3) as usize;
+            let (a, b) = (blade(&mut rng, grade_a), blade(&mut rng, grade_b));
+            assert_close(&a.wedge(&b), &(a * b).grade_part((grade_a + grade_b) as u32), 1e-4);
+            // Mixed-grade operands go through the per-grade-pair reference
+            let (m, n) = (random_multivector(&mut rng), random_multivector(&mut rng));
+            assert_close(&m.wedge(&n), &m.wedge_from_product(&n), 1e-4);
+        }
+    }
+
+    #[test]
+    fn vector_product_splits_into_inner_and_outer() {
+        let mut rng = XorShift64::new(166);
+        for _ in 0..50 {
+            let (a, b) = (random_vector(&mut rng), random_vector(&mut rng));
+            let split = Multivector5D::new_scalar(a.inner_product(&b)) + a.wedge(&b);
+            assert_close(&(a * b), &split, 1e-5);
+        }
+    }
+
+    /// `wedge` before the grade bookkeeping: every nonzero pair through the
+    /// Cayley table, kept when the result grade is the sum of the input grades.
+    fn wedge_by_grade_filter(a: &Multivector5D, b: &Multivector5D) -> Multivector5D {
+        let mut res = Multivector5D::zero();
+        let table = cayley_table(Multivector5D::DIM);
+        for (i, &a_val) in a.lanes.iter().enumerate() {
+            if a_val == 0.0 { continue; }
+            for (j, &b_val) in b.lanes.iter().enumerate() {
+                if b_val == 0.0 { continue; }
+                let (sign, k) = table[i * 32 + j];
+                if (k as u32).count_ones() == (i as u32).count_ones() + (j as u32).count_ones() {
+                    res.lanes[k] += sign * a_val * b_val;
+                }
+            }
+        }
+        res
+    }
+
+    #[test]
+    fn wedge_matches_grade_filter() {
+        let mut rng = XorShift64::new(115);
+        for _ in 0..200 {
+            let (a, b) = (random_multivector(&mut rng), random_multivector(&mut rng));
+            assert_eq!(a.wedge(&b).lanes, wedge_by_grade_filter(&a, &b).lanes);
+        }
+        for p in BOARD_SPACE.iter() {
+            let line = p.wedge(&Multivector5D::e(1));
+            assert_eq!(line.wedge(&Multivector5D::n_inf()).lanes, wedge_by_grade_filter(&line, &Multivector5D::n_inf()).lanes);
+        }
+    }
+
+    /// Times `f` over `iters` calls; run with `cargo test -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_wedge() {
+        let points: Vec<Multivector5D> = BOARD_SPACE.to_vec();
+        let dir = Multivector5D::e(1) + Multivector5D::e(2);
+        let mut sq = 0;
+        bench("wedge (grade filter)", 200_000, || {
+            sq = (sq + 1) % 64;
+            wedge_by_grade_filter(&wedge_by_grade_filter(&points[sq], &dir), &Multivector5D::n_inf()).lanes.iter().sum()
+        });
+        bench("wedge", 200_000, || {
+            sq = (sq + 1) % 64;
+            points[sq].wedge(&dir).wedge(&Multivector5D::n_inf()).lanes.iter().sum()
+        });
+    }
+
+    #[test]
+    fn in_place_combinators_match_operators() {
+        let mut rng = XorShift64::new(136);
+        for _ in 0..50 {
+            let (a, b) = (random_multivector(&mut rng), random_multivector(&mut rng));
+            let s = rng.next_f32() * 4.0 - 2.0;
+            let mut out = random_multivector(&mut rng);
+            a.add_into(&b, &mut out);
+            assert_eq!(out.lanes, (a + b).lanes);
+            a.scale_into(s, &mut out);
+            assert_eq!(out.lanes, (a * s).lanes);
+            let mut acc = a;
+            acc.add_assign_lanes(&b);
+            assert_eq!(acc.lanes, (a + b).lanes);
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn bench_in_place_combinators() {
+        let points: Vec<Multivector5D> = BOARD_SPACE.to_vec();
+        let mut sq = 0;
+        bench("a + b * s", 1_000_000, || {
+            sq = (sq + 1) % 63;
+            (points[sq] + points[sq + 1] * 0.5).lanes[1]
+        });
+        let (mut scaled, mut out) = (Multivector5D::zero(), Multivector5D::zero());
+        bench("scale_into + add_into", 1_000_000, || {
+            sq = (sq + 1) % 63;
+            points[sq + 1].scale_into(0.5, &mut scaled);
+            points[sq].add_into(&scaled, &mut out);
+            out.lanes[1]
+        });
+    }
+
+    /// Every board point against every line blade through every square.
+    fn board_lines() -> Vec<Multivector5D> {
+        let dirs = [(1.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, -1.0)];
+        BOARD_SPACE
+            .iter()
+            .flat_map(|p| dirs.iter().map(move |&(dx, dy)| Multivector5D::line_blade(p, dx, dy)))
+            .collect()
+    }
+
+    #[test]
+    fn sparse_blades_match_dense() {
+        let mut rng = XorShift64::new(138);
+        for _ in 0..50 {
+            let m = random_multivector(&mut rng).grade_part(2);
+            assert_eq!(SparseBlade::from_dense(&m).to_dense().lanes, m.lanes);
+        }
+        let lines = board_lines();
+        let sparse_lines: Vec<SparseBlade> = lines.iter().map(SparseBlade::from_dense).collect();
+        for (line, sparse_line) in lines.iter().zip(&sparse_lines) {
+            for (point, sparse_point) in BOARD_SPACE.iter().zip(SPARSE_BOARD_SPACE.iter()) {
+                assert_eq!(sparse_line.is_incident(sparse_point), line.is_incident(point));
+                assert_eq!(sparse_point.inner_product(line), point.inner_product(line));
+            }
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn bench_sparse_incidence() {
+        let lines = board_lines();
+        let sparse_lines: Vec<SparseBlade> = lines.iter().map(SparseBlade::from_dense).collect();
+        bench("dense incidence, full board", 200, || {
+            let mut hits = 0;
+            for line in &lines {
+                hits += BOARD_SPACE.iter().filter(|p| line.is_incident(p)).count();
+            }
+            hits as f32
+        });
+        bench("sparse incidence, full board", 200, || {
+            let mut hits = 0;
+            for line in &sparse_lines {
+                hits += SPARSE_BOARD_SPACE.iter().filter(|p| line.is_incident(p)).count();
+            }
+            hits as f32
+        });
+    }
+
+    #[test]
+    fn batch_product_matches_loop() {
+        let mut rng = XorShift64::new(144);
+        let versor = board_symmetry_versor(BoardSymmetry::Rotate90);
+        let random = random_multivector(&mut rng);
+        let others: Vec<Multivector5D> = (0..40).map(|_| random_multivector(&mut rng)).collect();
+        for left in [versor, random] {
+            let mut out = vec![Multivector5D::zero(); others.len()];
+            left.geometric_product_batch(&others, &mut out);
+            for (o, m) in out.iter().zip(&others) {
+                assert_close(o, &left.geometric_product(m), 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn bench_batch_product() {
+        let versor = board_symmetry_versor(BoardSymmetry::Rotate90);
+        let points: Vec<Multivector5D> = BOARD_SPACE.to_vec();
+        let mut out = vec![Multivector5D::zero(); 64];
+        bench("64 single products", 20_000, || {
+            for (o, p) in out.iter_mut().zip(&points) {
+                *o = versor.geometric_product(p);
+            }
+            out[9].lanes[1]
+        });
+        bench("one 64-wide batch", 20_000, || {
+            versor.geometric_product_batch(&points, &mut out);
+            out[9].lanes[1]
+        });
+    }
+}
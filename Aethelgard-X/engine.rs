@@ -1,13 +1,38 @@
+use std::io::Write;
+use std::time::Instant;
 use cozy_chess::*;
-use crate::shadow::ShadowGuard;
-use crate::field::GeodesicField;
+use crate::shadow::{terminal_score, ShadowGuard, MATE_SCORE};
+use crate::tt::MAX_PLY;
+use crate::field::{GeodesicField, StrategicPlan};
 use crate::eval::GeotensorEvaluator;
+use crate::material::Material;
+use crate::error::GeoLlamaError;
+
+/// UCI score token: `cp <n>`, or `mate <moves>` (negative when being mated).
+pub fn uci_score(score: i32) -> String {
+    if score.abs() > MATE_SCORE - MAX_PLY as i32 {
+        let moves = (MATE_SCORE - score.abs() + 1) / 2;
+        format!("mate {}", if score > 0 { moves } else { -moves })
+    } else {
+        format!("cp {}", score)
+    }
+}
+
+/// Geotensor evaluations within this many centipawns of the best safe move
+/// count as equal in `decide_move`, leaving the choice to tactics.
+const FUSION_TIE_MARGIN: i32 = 10;
+
+/// Plies `is_blunder` looks ahead from the root, counting the move itself: the
+/// opponent's reply and our recapture.
+const BLUNDER_CHECK_PLIES: i32 = 3;
 
 pub struct AethelgardX {
     pub board: Board,
     pub shadow: ShadowGuard,
     pub evaluator: GeotensorEvaluator,
     pub nodes: u64,
+    /// Plan carried across moves; followed while it stays tactically safe.
+    pub plan: Option<StrategicPlan>,
 }
 
 impl AethelgardX {
@@ -17,16 +42,45 @@ impl AethelgardX {
             shadow: ShadowGuard::new(),
             evaluator: GeotensorEvaluator::new(None),
             nodes: 0,
+            plan: None,
+        }
+    }
+
+    /// Best move for `self.board`, or an error when the game is already over.
+    pub fn get_best_move(&mut self) -> Result<Move, GeoLlamaError> {
+        if !self.board.generate_moves(|_| true) {
+            return Err(GeoLlamaError::NoLegalMoves(self.board.status()));
         }
+        let side = self.board.side_to_move();
+        self.with_tension_side(side, Self::choose_move)
     }
 
-    pub fn get_best_move(&mut self) -> Move {
+    /// Runs `f` with the evaluator's tension preference held by `side`, the
+    /// root side, whatever the side to move in the evaluated positions; the
+    /// previous setting is restored afterwards.
+    fn with_tension_side<T>(&mut self, side: Color, f: impl FnOnce(&mut Self) -> T) -> T {
+        let previous = self.evaluator.tension_side.replace(side);
+        let result = f(self);
+        self.evaluator.tension_side = previous;
+        result
+    }
+
+    fn choose_move(&mut self) -> Result<Move, GeoLlamaError> {
         let side = self.board.side_to_move();
         let enemy_king_sq = self.board.king(!side);
         
         let mut field = GeodesicField::new();
         field.update_costs(&self.board);
 
+        if let Some(plan) = self.plan.as_mut() {
+            if let Some(mv) = plan.advance(&self.board, &field) {
+                if self.shadow.verify_move(&self.board, mv) {
+                    return Ok(mv);
+                }
+            }
+            self.plan = None;
+        }
+
         let active_sqs = self.get_our_piece_squares();
         if active_sqs.is_empty() {
             return self.standard_search_fallback();
@@ -38,7 +92,7 @@ impl AethelgardX {
             let piece_type = self.board.piece_on(Square::index(active_sqs[0]));
             
             // Primal Wave (from us)
-            field.propagate(&active_sqs, piece_type, &self.board);
+            field.propagate(&active_sqs, piece_type, &self.board, f32::MAX);
             // Retrocausal Wave (from Enemy King)
             field.propagate_retro(enemy_king_sq as usize, &self.board);
             
@@ -46,12 +100,13 @@ impl AethelgardX {
                 if let Some(mv) = self.find_move_to_target(target_sq) {
                     let feedback = self.shadow.probe_tactics(&self.board, mv);
                     if feedback.is_safe || attempts > 10 {
-                        return mv;
+                        self.plan = self.plan_after(mv);
+                        return Ok(mv);
                     } else {
                         // TACTICAL BLUNDER DETECTED: Project into manifold
                         for (sq, mass) in feedback.danger_squares {
-                            let current_barrier = field.barriers.entry(sq).or_insert(0.0);
-                            *current_barrier += mass;
+                            // Danger masses are fixed positive constants, never rejected
+                            let _ = field.add_barrier(sq, mass);
                         }
                     }
                 } else {
@@ -69,6 +124,191 @@ impl AethelgardX {
         }
     }
 
+    /// Plan for the piece `mv` moves on `self.board`: keep routing it toward
+    /// the enemy king along its own geodesic. Pawns and the king get none.
+    fn plan_after(&self, mv: Move) -> Option<StrategicPlan> {
+        let piece = match mv.promotion.or(self.board.piece_on(mv.from))? {
+            Piece::Pawn | Piece::King => return None,
+            piece => piece,
+        };
+        let enemy_king = self.board.king(!self.board.side_to_move());
+        Some(StrategicPlan::new(piece, mv.to as usize, enemy_king as usize))
+    }
+
+    /// Fuses the strategic and tactical views into one choice:
+    /// 1. Root moves are ranked by the Geotensor evaluation of the position they
+    ///    lead to, from our side.
+    /// 2. Going down that ranking, each move gets a ShadowGuard probe; vetoed
+    ///    moves (blunders) are skipped.
+    /// 3. The first safe move opens a band `FUSION_TIE_MARGIN` centipawns wide;
+    ///    safe moves inside it compete on their tactical score, and the
+    ///    Geotensor rank breaks any remaining tie.
+    /// 4. If every move is vetoed, the tactically least bad one is played.
+    ///
+    /// The evaluator's `tension_weight` is applied for the side to move on `board`.
+    pub fn decide_move(&mut self, board: &Board) -> Result<Move, GeoLlamaError> {
+        self.with_tension_side(board.side_to_move(), |engine| engine.fuse_decision(board))
+    }
+
+    fn fuse_decision(&mut self, board: &Board) -> Result<Move, GeoLlamaError> {
+        let mut moves = Vec::new();
+        board.generate_moves(|mvs| {
+            moves.extend(mvs);
+            false
+        });
+        if moves.is_empty() {
+            return Err(GeoLlamaError::NoLegalMoves(board.status()));
+        }
+        let root_material = Material::from_board(board);
+        let mut ranked: Vec<(Move, i32)> = moves
+            .into_iter()
+            .map(|mv| {
+                let mut next_board = board.clone();
+                next_board.play(mv);
+                let material = root_material.after_move(board, mv);
+                (mv, -self.evaluator.evaluate_with_material(&next_board, material))
+            })
+            .collect();
+        // Stable, so equal evaluations keep move generation order
+        ranked.sort_by_key(|&(_, geo)| std::cmp::Reverse(geo));
+
+        let mut band_floor = None;
+        let mut best: Option<(i32, Move)> = None;
+        let mut least_bad: Option<(i32, Move)> = None;
+        for (mv, geo) in ranked {
+            if band_floor.is_some_and(|floor| geo < floor) {
+                break;
+            }
+            let feedback = self.shadow.probe_tactics(board, mv);
+            let slot = if feedback.is_safe {
+                band_floor.get_or_insert(geo - FUSION_TIE_MARGIN);
+                &mut best
+            } else {
+                &mut least_bad
+            };
+            // Strictly greater, so the earlier (strategically better) move keeps ties
+            if slot.is_none_or(|(score, _)| feedback.score > score) {
+                *slot = Some((feedback.score, mv));
+            }
+        }
+        // The top-ranked move is always probed, so one of the two is set
+        best.or(least_bad).map(|(_, mv)| mv).ok_or(GeoLlamaError::NoLegalMoves(board.status()))
+    }
+
+    /// Strategic sanity check independent of the ShadowGuard depth: projects
+    /// every root move `BLUNDER_CHECK_PLIES` plies ahead with the Geotensor
+    /// evaluation and flags `mv` if some other move projects more than `margin`
+    /// centipawns better. Illegal moves are always blunders.
+    pub fn is_blunder(&mut self, board: &Board, mv: Move, margin: i32) -> bool {
+        if !board.is_legal(mv) {
+            return true;
+        }
+        self.with_tension_side(board.side_to_move(), |engine| engine.outplayed_by_margin(board, mv, margin))
+    }
+
+    fn outplayed_by_margin(&mut self, board: &Board, mv: Move, margin: i32) -> bool {
+        let root_material = Material::from_board(board);
+        // Exact above `floor`; at or below it only the bound is known
+        let project = |engine: &mut Self, mv: Move, floor: i32| {
+            let mut next_board = board.clone();
+            next_board.play(mv);
+            let material = root_material.after_move(board, mv);
+            -engine.advanced_search(&next_board, material, BLUNDER_CHECK_PLIES - 1, 1, -i32::MAX, -floor)
+        };
+        let threshold = project(self, mv, -i32::MAX).saturating_add(margin);
+
+        let mut moves = Vec::new();
+        board.generate_moves(|mvs| {
+            moves.extend(mvs);
+            false
+        });
+        // Only whether a move clears the threshold matters, so each search may cut off there
+        moves.into_iter().any(|other| other != mv && project(self, other, threshold) > threshold)
+    }
+
+    /// ShadowGuard iterative deepening to `depth`, streaming one UCI `info` line
+    /// per completed iteration (depth, score, nodes, nps, time, pv) followed by
+    /// an `info string` with the Geotensor breakdown of the PV's end position,
+    /// from the root side's view. Returns the final score and move.
+    pub fn search_with_info(&mut self, board: &Board, depth: i32, out: &mut impl Write) -> (i32, Option<Move>) {
+        let start = Instant::now();
+        let evaluator = &mut self.evaluator;
+        self.shadow.last_iteration = None;
+        self.shadow.search_iterations(board, depth.max(1), |shadow, reached, result| {
+            let pv = shadow.principal_variation(board, reached as usize);
+            let mut leaf = board.clone();
+            for &mv in &pv {
+                leaf.play(mv);
+            }
+            let mut parts = evaluator.breakdown(&leaf);
+            if pv.len() % 2 == 1 {
+                parts = -parts;
+            }
+
+            let millis = start.elapsed().as_millis() as u64;
+            let nps = shadow.nodes * 1000 / millis.max(1);
+            let pv_text: Vec<String> = pv.iter().map(|mv| mv.to_string()).collect();
+            // A sink that stops accepting lines shouldn't abort the search
+            let _ = writeln!(
+                out,
+                "info depth {} score {} nodes {} nps {} time {} pv {}",
+                reached, uci_score(result.0), shadow.nodes, nps, millis, pv_text.join(" ")
+            );
+            let _ = writeln!(
+                out,
+                "info string material {:.0} vision {:.1} mps {:.1} entropy {:.1}",
+                // + 0.0 turns a negated zero back into 0, so it doesn't print as "-0"
+                parts.material + 0.0, parts.vision + 0.0, parts.mps + 0.0, parts.entropy + 0.0
+            );
+        })
+    }
+
+    /// Everything a front-end needs for heatmaps of `board`, as one JSON object.
+    /// Each field is a 64-element array indexed a1 = 0 .. h8 = 63; unreachable
+    /// geodesic squares are `null`. `danger` is the heaviest danger mass the
+    /// tactical probe assigns to a square over all legal moves, so this runs a
+    /// shallow search per move.
+    pub fn export_analysis_json(&mut self, board: &Board) -> String {
+        let side = board.side_to_move();
+        let ours: Vec<usize> = board.colors(side).into_iter().map(|sq| sq as usize).collect();
+        let mut field = GeodesicField::new();
+        field.update_costs(board);
+        if let Some(&first) = ours.first() {
+            field.propagate(&ours, board.piece_on(Square::index(first)), board, f32::MAX);
+        }
+        field.propagate_retro(board.king(!side) as usize, board);
+
+        let mut moves = Vec::new();
+        board.generate_moves(|mvs| {
+            moves.extend(mvs);
+            false
+        });
+        let mut danger = [0.0f32; 64];
+        for mv in moves {
+            for (sq, mass) in self.shadow.probe_tactics(board, mv).danger_squares {
+                danger[sq] = danger[sq].max(mass);
+            }
+        }
+
+        let fields = [
+            ("mps_environment", self.evaluator.mps_site_amplitudes(board)),
+            ("potential", field.potentials),
+            ("retro_potential", field.retro_potentials),
+            ("vision", self.evaluator.vision_by_square(board)),
+            ("danger", danger),
+        ];
+        let mut json = format!("{{\"fen\":\"{}\",\"side_to_move\":\"{}\"", board, if side == Color::White { "white" } else { "black" });
+        for (name, values) in fields {
+            let items: Vec<String> = values
+                .iter()
+                .map(|&v| if v.is_finite() && v < f32::MAX { format!("{}", v) } else { "null".to_string() })
+                .collect();
+            json += &format!(",\"{}\":[{}]", name, items.join(","));
+        }
+        json.push('}');
+        json
+    }
+
     fn get_our_piece_squares(&self) -> Vec<usize> {
         self.board.colors(self.board.side_to_move())
             .into_iter()
@@ -90,7 +330,7 @@ impl AethelgardX {
         best_move
     }
 
-    fn standard_search_fallback(&mut self) -> Move {
+    fn standard_search_fallback(&mut self) -> Result<Move, GeoLlamaError> {
         let mut best_move = None;
         let mut best_score = -i32::MAX;
         
@@ -102,23 +342,25 @@ impl AethelgardX {
             false
         });
 
+        let root_material = Material::from_board(&self.board);
         for mv in moves {
             let mut next_board = self.board.clone();
             next_board.play(mv);
             // Use the advanced evaluator for the fallback search too
-            let score = -self.advanced_search(&next_board, 3, -i32::MAX, i32::MAX);
+            let material = root_material.after_move(&self.board, mv);
+            let score = -self.advanced_search(&next_board, material, 3, 1, -i32::MAX, i32::MAX);
             if score > best_score {
                 best_score = score;
                 best_move = Some(mv);
             }
         }
-        best_move.expect("No legal moves!")
+        best_move.ok_or(GeoLlamaError::NoLegalMoves(self.board.status()))
     }
 
-    fn advanced_search(&mut self, board: &Board, depth: i32, mut alpha: i32, beta: i32) -> i32 {
+    fn advanced_search(&mut self, board: &Board, material: Material, depth: i32, ply: i32, mut alpha: i32, beta: i32) -> i32 {
         self.nodes += 1;
         if depth == 0 {
-            return self.evaluator.evaluate(board);
+            return self.evaluator.evaluate_with_material(board, material);
         }
 
         let mut moves = Vec::new();
@@ -130,14 +372,14 @@ impl AethelgardX {
         });
 
         if moves.is_empty() {
-            return if board.status() == GameStatus::Drawn { 0 } else { -20000 };
+            return terminal_score(board, ply);
         }
 
         let mut best_score = -i32::MAX;
         for mv in moves {
             let mut next_board = board.clone();
             next_board.play(mv);
-            let score = -self.advanced_search(&next_board, depth - 1, -beta, -alpha);
+            let score = -self.advanced_search(&next_board, material.after_move(board, mv), depth - 1, ply + 1, -beta, -alpha);
             if score >= beta { return beta; }
             if score > best_score {
                 best_score = score;
@@ -147,3 +389,133 @@ impl AethelgardX {
         best_score
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::EvalFlags;
+    use crate::material::piece_value;
+
+    #[test]
+    fn finished_games_report_no_legal_moves() {
+        let stalemate: Board = "7k/5Q2/6K1/8/8/8/8/8 b - - 0 1".parse().unwrap();
+        let mut engine = AethelgardX::new();
+        assert!(matches!(engine.decide_move(&stalemate), Err(GeoLlamaError::NoLegalMoves(GameStatus::Drawn))));
+        engine.board = stalemate;
+        assert!(matches!(engine.get_best_move(), Err(GeoLlamaError::NoLegalMoves(GameStatus::Drawn))));
+    }
+
+    #[test]
+    fn plan_persists_across_moves() {
+        let mut engine = AethelgardX::new();
+        engine.board = "4k3/pppp4/8/8/8/8/8/1N2K3 w - - 0 1".parse().unwrap();
+        let first = engine.get_best_move().unwrap();
+        let plan = engine.plan.as_ref().expect("the knight move starts a plan");
+        assert_eq!((plan.piece, plan.square), (Piece::Knight, first.to as usize));
+
+        engine.board.play(first);
+        engine.board.play("a7a6".parse().unwrap());
+        let second = engine.get_best_move().unwrap();
+        assert_eq!(second.from, first.to);
+        let plan = engine.plan.as_ref().expect("the plan is still being followed");
+        assert_eq!(plan.square, second.to as usize);
+    }
+
+    #[test]
+    fn info_lines_are_well_formed() {
+        let board = Board::default();
+        let mut engine = AethelgardX::new();
+        let mut out = Vec::new();
+        let (_, best) = engine.search_with_info(&board, 4, &mut out);
+        let text = String::from_utf8(out).unwrap();
+        let infos: Vec<Vec<&str>> = text
+            .lines()
+            .filter(|line| !line.starts_with("info string"))
+            .map(|line| line.split_whitespace().collect())
+            .collect();
+        assert_eq!(infos.len(), 4);
+        assert_eq!(text.lines().filter(|line| line.starts_with("info string")).count(), 4);
+
+        let mut last_depth = 0;
+        for info in &infos {
+            assert_eq!(info[..2], ["info", "depth"]);
+            let depth: i32 = info[2].parse().unwrap();
+            assert!(depth >= last_depth);
+            last_depth = depth;
+            assert_eq!(info[3], "score");
+            assert!(info[4] == "cp" || info[4] == "mate");
+            info[5].parse::<i32>().unwrap();
+            for (key, value) in [("nodes", 7), ("nps", 9), ("time", 11)] {
+                assert_eq!(info[value - 1], key);
+                info[value].parse::<u64>().unwrap();
+            }
+            assert_eq!(info[12], "pv");
+            let mut line = board.clone();
+            for mv in &info[13..] {
+                line.try_play(mv.parse().unwrap()).unwrap();
+            }
+        }
+        assert_eq!(infos[3][13].parse::<Move>().ok(), best);
+    }
+
+    /// Qxc5 takes a loose pawn, but Nd3+ forks king and queen.
+    const FORK_BAIT: &str = "4k2r/pp4pp/8/2p1n3/8/8/PP4PP/2Q1K3 w - - 0 1";
+
+    #[test]
+    fn decisions_avoid_the_forked_queen() {
+        let board: Board = FORK_BAIT.parse().unwrap();
+        let mut engine = AethelgardX::new();
+        let mv = engine.decide_move(&board).unwrap();
+        assert_ne!(mv, "c1c5".parse().unwrap());
+        assert!(engine.shadow.verify_move(&board, mv));
+    }
+
+    #[test]
+    fn tension_weight_picks_the_tense_or_the_quiet_move() {
+        // Nothing hangs, so every move is safe and the entropy term alone ranks them
+        let board: Board = "8/8/8/4k3/8/8/8/4K2R w - - 0 1".parse().unwrap();
+        let mut engine = AethelgardX::new();
+        engine.evaluator = GeotensorEvaluator::new_seeded(153);
+        engine.evaluator.quiet_gate = false;
+        engine.evaluator.enabled_components = EvalFlags::ENTROPY;
+        engine.evaluator.weights.endgame_pieces = 0;
+
+        let mut entropies: Vec<(f32, Move)> = Vec::new();
+        board.generate_moves(|mvs| {
+            entropies.extend(mvs.into_iter().map(|mv| (0.0, mv)));
+            false
+        });
+        engine.evaluator.tension_weight = 1.0;
+        for (entropy, mv) in entropies.iter_mut() {
+            let mut child = board.clone();
+            child.play(*mv);
+            *entropy = engine.evaluator.breakdown(&child).entropy;
+        }
+        entropies.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let (quietest, tensest) = (entropies[0], entropies[entropies.len() - 1]);
+        assert!(tensest.0 > quietest.0);
+
+        // Far outweighs the tie band, so tactics don't get a say
+        let weight = 1000.0 * FUSION_TIE_MARGIN as f32 / (tensest.0 - quietest.0);
+        engine.evaluator.tension_weight = weight;
+        assert_eq!(engine.decide_move(&board).unwrap(), tensest.1);
+        engine.evaluator.tension_weight = -weight;
+        assert_eq!(engine.decide_move(&board).unwrap(), quietest.1);
+        // The root side is only held for the decision
+        assert_eq!(engine.evaluator.tension_side, None);
+    }
+
+    #[test]
+    fn blunders_are_measured_against_the_margin() {
+        // Qxd5 exd5 loses the queen for a pawn within the check's horizon
+        let board: Board = "4k3/8/4p3/3p4/8/8/8/3QK3 w - - 0 1".parse().unwrap();
+        let greedy: Move = "d1d5".parse().unwrap();
+        let mut engine = AethelgardX::new();
+        assert!(engine.is_blunder(&board, greedy, piece_value(Piece::Knight)));
+        assert!(!engine.is_blunder(&board, greedy, 2 * piece_value(Piece::Queen)));
+        // A quiet king step gives up no more than positional crumbs
+        assert!(!engine.is_blunder(&board, "e1e2".parse().unwrap(), piece_value(Piece::Pawn)));
+        // Illegal moves always count
+        assert!(engine.is_blunder(&board, "e1e3".parse().unwrap(), i32::MAX));
+    }
+}
@@ -0,0 +1,62 @@
+use std::fmt;
+use cozy_chess::*;
+use crate::eval::EvalInitError;
+
+/// Crate-wide error for the fallible public entry points. Panics are kept for
+/// broken internal invariants; anything bad input can cause ends up here.
+#[derive(Debug)]
+pub enum GeoLlamaError {
+    /// The FEN string could not be parsed into a position
+    InvalidFen(FenParseError),
+    /// A move string is not UCI long algebraic notation
+    InvalidMove(String),
+    /// A well-formed move that isn't legal in the position it was played in
+    IllegalMove(Move),
+    /// The side to move has no legal moves, so there is nothing to choose
+    NoLegalMoves(GameStatus),
+    /// Evaluator tensors were rejected while building or loading
+    EvalInit(EvalInitError),
+    /// A geodesic barrier that is off the board, negative or not finite
+    InvalidBarrier { square: usize, mass: f32 },
+    /// A geodesic step-cost multiplier that is negative or not finite
+    InvalidStepCost { piece: Piece, cost: f32 },
+    /// A king-gravity strength that is negative or not finite
+    InvalidKingGravity(f32),
+}
+
+impl fmt::Display for GeoLlamaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GeoLlamaError::InvalidFen(e) => write!(f, "invalid FEN: {}", e),
+            GeoLlamaError::InvalidMove(text) => write!(f, "invalid move {:?}", text),
+            GeoLlamaError::IllegalMove(mv) => write!(f, "illegal move {}", mv),
+            GeoLlamaError::NoLegalMoves(status) => write!(f, "no legal moves ({:?})", status),
+            GeoLlamaError::EvalInit(e) => write!(f, "invalid evaluator tensors: {}", e),
+            GeoLlamaError::InvalidBarrier { square, mass } => write!(f, "invalid barrier {} on square {}", mass, square),
+            GeoLlamaError::InvalidStepCost { piece, cost } => write!(f, "invalid step cost {} for {:?}", cost, piece),
+            GeoLlamaError::InvalidKingGravity(strength) => write!(f, "invalid king gravity {}", strength),
+        }
+    }
+}
+
+impl std::error::Error for GeoLlamaError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        // cozy-chess errors only implement Error with its `std` feature
+        match self {
+            GeoLlamaError::EvalInit(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<FenParseError> for GeoLlamaError {
+    fn from(e: FenParseError) -> Self {
+        GeoLlamaError::InvalidFen(e)
+    }
+}
+
+impl From<EvalInitError> for GeoLlamaError {
+    fn from(e: EvalInitError) -> Self {
+        GeoLlamaError::EvalInit(e)
+    }
+}
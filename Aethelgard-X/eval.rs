@@ -1,6 +1,20 @@
 use cozy_chess::*;
-use crate::cga::BOARD_SPACE;
+use crate::cga::{Multivector5D, SparseBlade, BOARD_SPACE, SPARSE_BOARD_SPACE};
+use crate::linalg;
+use crate::rng::{RngSource, XorShift64};
+use crate::error::GeoLlamaError;
+use crate::util::is_quiet;
+use crate::material::{endgame_king_term, is_insufficient_material, passed_pawn_score, piece_value, Material};
+use crate::field::{GeodesicField, KNIGHT_ADJACENCY};
 
+/// Bond dimension (chi) of the tensor network.
+pub const CHI: usize = 10;
+/// Physical dimension: Empty + 6 piece types for each color.
+pub const PHYSICAL_DIM: usize = 13;
+/// Below this pre-normalization norm an MPS state has collapsed and is reset
+const MPS_DEGENERATE_NORM: f32 = 1e-9;
+
+#[derive(Clone)]
 pub struct SquareTensor {
     // Physical dimension d=13 (Empty, P, N, B, R, Q, K * White/Black)
     // Bond dimension chi=10
@@ -8,76 +22,902 @@ pub struct SquareTensor {
     pub data: [[[f32; 10]; 10]; 13],
 }
 
+impl SquareTensor {
+    /// Number of values in a flat tensor: PHYSICAL_DIM * CHI * CHI.
+    pub const LEN: usize = PHYSICAL_DIM * CHI * CHI;
+
+    /// Builds a tensor from a flat row-major slice laid out as
+    /// `[physical][bond_left][bond_right]`, i.e. value `(p, l, r)` sits at
+    /// `p * CHI * CHI + l * CHI + r`. Returns None unless the slice has `LEN` values.
+    pub fn from_slice(values: &[f32]) -> Option<Self> {
+        if values.len() != Self::LEN {
+            return None;
+        }
+        let mut data = [[[0.0; CHI]; CHI]; PHYSICAL_DIM];
+        for (i, &v) in values.iter().enumerate() {
+            data[i / (CHI * CHI)][(i / CHI) % CHI][i % CHI] = v;
+        }
+        Some(Self { data })
+    }
+
+    /// Identity bond for every physical index, so the square passes the MPS
+    /// state through unchanged whatever stands on it.
+    pub fn identity() -> Self {
+        let mut data = [[[0.0; CHI]; CHI]; PHYSICAL_DIM];
+        for slice in data.iter_mut() {
+            for (c, row) in slice.iter_mut().enumerate() {
+                row[c] = 1.0;
+            }
+        }
+        Self { data }
+    }
+
+    /// Uniform random entries in [-0.5, 0.5), then `normalize`d.
+    pub fn random(rng: &mut (impl RngSource + ?Sized)) -> Self {
+        let mut data = [[[0.0; CHI]; CHI]; PHYSICAL_DIM];
+        for v in data.iter_mut().flatten().flatten() {
+            *v = rng.next_f32() - 0.5;
+        }
+        let mut tensor = Self { data };
+        tensor.normalize();
+        tensor
+    }
+
+    /// Scales the whole tensor so its largest physical slice has the Frobenius
+    /// norm of the CHI x CHI identity, keeping the contraction's state from
+    /// growing or shrinking geometrically along the chain. The identity is
+    /// unchanged; an all-zero tensor is left alone.
+    pub fn normalize(&mut self) {
+        let largest = self
+            .data
+            .iter()
+            .map(|slice| slice.iter().flatten().map(|v| v * v).sum::<f32>())
+            .fold(0.0, f32::max)
+            .sqrt();
+        if largest > 0.0 {
+            let scale = (CHI as f32).sqrt() / largest;
+            for v in self.data.iter_mut().flatten().flatten() {
+                *v *= scale;
+            }
+        }
+    }
+}
+
+/// Bond rows of the packed layout are padded to this many lanes (two AVX or
+/// four NEON registers), so the contraction's inner loop has no remainder.
+#[cfg(feature = "simd")]
+const PACKED_LANES: usize = 16;
+
+/// A square tensor with each `[bond_left]` row padded to `PACKED_LANES`
+/// zero-filled lanes, keeping the output bond contiguous for SIMD.
+#[cfg(feature = "simd")]
+#[derive(Clone)]
+struct PackedTensor {
+    rows: [[[f32; PACKED_LANES]; CHI]; PHYSICAL_DIM],
+}
+
+#[cfg(feature = "simd")]
+impl PackedTensor {
+    fn new(tensor: &SquareTensor) -> Self {
+        let mut rows = [[[0.0; PACKED_LANES]; CHI]; PHYSICAL_DIM];
+        for (packed, plain) in rows.iter_mut().zip(tensor.data.iter()) {
+            for (row, src) in packed.iter_mut().zip(plain.iter()) {
+                row[..CHI].copy_from_slice(src);
+            }
+        }
+        Self { rows }
+    }
+}
+
+/// Tunable constants for the geometric evaluation terms.
+#[derive(Clone, Copy, Debug)]
+pub struct EvalWeights {
+    /// Vision score per point of value when a blade hits an enemy piece
+    pub vision_hit: f32,
+    /// Vision score per point of value for an x-ray through a friendly piece
+    pub vision_xray: f32,
+    /// Fraction of opacity that passes through a friendly piece
+    pub friendly_transparency: f32,
+    /// Fraction of opacity that passes through a friendly slider backing up the
+    /// ray (rook/queen on a file or rank, bishop/queen on a diagonal). Higher than
+    /// `friendly_transparency` so doubled rooks or queen+rook batteries keep
+    /// scoring past the second piece instead of hitting the cutoff.
+    pub battery_transparency: f32,
+    /// Rays are abandoned once opacity falls below this
+    pub opacity_cutoff: f32,
+    /// Distance decay of vision: a target `d` squares away (Euclidean) counts
+    /// `1 / (1 + vision_distance_decay * d)`. 0 weighs every distance alike.
+    pub vision_distance_decay: f32,
+    /// Score per centipawn a knight fork is expected to win
+    pub knight_fork: f32,
+    /// Activity bonus for a piece standing on the enemy king, decaying as
+    /// 1 / (1 + d); applied in `evaluate_with_field` (0 = off)
+    pub piece_activity: f32,
+    /// Weight of the geodesic interference tension (0 = off)
+    pub interference_tension: f32,
+    /// Weight of the retro-field king tropism (0 = off)
+    pub king_tropism: f32,
+    /// Weight of the geodesic mobility difference (0 = off). Not free: every
+    /// evaluation then builds a fresh field per side and runs one Dijkstra per
+    /// piece (see `side_mobility`), which slows the search noticeably.
+    pub mobility: f32,
+    /// Bound on the MPS term: it contributes `mps_scale * tanh(2 * amplitude)`
+    pub mps_scale: f32,
+    /// Below this many pieces (kings included) the geometric terms fade out
+    /// linearly, reaching 0 with bare kings, and king activity fades in
+    pub endgame_pieces: u32,
+    /// King activity per step of centralization and of closeness to the pawns,
+    /// at full weight once the geometric terms have faded out
+    pub king_activity: f32,
+    /// Penalty per piece giving check to the side to move, doubled for a
+    /// contact check from an adjacent square
+    pub check_penalty: f32,
+    /// Factor on the vision terms while the side to move is in check
+    pub check_vision_scale: f32,
+    /// Weight of `passed_pawn_score` (0 = off)
+    pub passed_pawn: f32,
+}
+
+impl Default for EvalWeights {
+    fn default() -> Self {
+        Self {
+            vision_hit: 5.0,
+            vision_xray: 0.5,
+            friendly_transparency: 0.2,
+            battery_transparency: 0.8,
+            opacity_cutoff: 0.05,
+            vision_distance_decay: 0.0,
+            knight_fork: 0.1,
+            piece_activity: 0.0,
+            interference_tension: 0.0,
+            king_tropism: 0.0,
+            mobility: 0.0,
+            mps_scale: 50.0,
+            endgame_pieces: 12,
+            king_activity: 10.0,
+            check_penalty: 30.0,
+            check_vision_scale: 0.5,
+            passed_pawn: 1.0,
+        }
+    }
+}
+
+/// Why externally supplied tensors were rejected; reported as `GeoLlamaError::EvalInit`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvalInitError {
+    /// Neither 64 (one per square) nor 32 (mirror-tied) tensors were given
+    WrongTensorCount(usize),
+    /// The flat value count is not a whole number of `SquareTensor::LEN` blocks
+    RaggedTensorData(usize),
+    /// A NaN or infinite value, which would poison every contraction
+    NonFiniteValue { tensor: usize, index: usize },
+    /// A tensor file's byte length is not a whole number of f32 values
+    PartialValue(usize),
+    /// The tensor file could not be read
+    Io(std::io::ErrorKind),
+}
+
+impl std::fmt::Display for EvalInitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            EvalInitError::WrongTensorCount(n) => write!(f, "expected 64 or 32 square tensors, got {}", n),
+            EvalInitError::RaggedTensorData(n) => {
+                write!(f, "{} values is not a multiple of the tensor size {}", n, SquareTensor::LEN)
+            }
+            EvalInitError::NonFiniteValue { tensor, index } => {
+                write!(f, "tensor {} has a non-finite value at flat index {}", tensor, index)
+            }
+            EvalInitError::PartialValue(n) => write!(f, "{} bytes is not a whole number of f32 values", n),
+            EvalInitError::Io(kind) => write!(f, "could not read tensor file: {}", kind),
+        }
+    }
+}
+
+impl std::error::Error for EvalInitError {}
+
+/// Evaluation split into its terms (centipawns, side to move); `total` is what
+/// `evaluate` returns.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct EvalBreakdown {
+    pub material: f32,
+    pub vision: f32,
+    pub knight_forks: f32,
+    pub mps: f32,
+    pub entropy: f32,
+    pub king_activity: f32,
+    pub check: f32,
+    pub mobility: f32,
+    pub endgame_king: f32,
+    pub passed_pawns: f32,
+    pub total: i32,
+}
+
+/// The same breakdown from the other side's point of view.
+impl std::ops::Neg for EvalBreakdown {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self {
+            material: -self.material,
+            vision: -self.vision,
+            knight_forks: -self.knight_forks,
+            mps: -self.mps,
+            entropy: -self.entropy,
+            king_activity: -self.king_activity,
+            check: -self.check,
+            mobility: -self.mobility,
+            endgame_king: -self.endgame_king,
+            passed_pawns: -self.passed_pawns,
+            total: -self.total,
+        }
+    }
+}
+
+/// Set of evaluation terms to compute; disabled terms are skipped entirely.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EvalFlags(u16);
+
+impl EvalFlags {
+    pub const MATERIAL: Self = Self(1 << 0);
+    /// CGA vision and knight forks
+    pub const VISION: Self = Self(1 << 1);
+    pub const MPS: Self = Self(1 << 2);
+    pub const ENTROPY: Self = Self(1 << 3);
+    /// Interference tension in `evaluate_with_field`
+    pub const GEODESIC: Self = Self(1 << 4);
+    /// Geodesic piece activity in `evaluate_with_field`
+    pub const ACTIVITY: Self = Self(1 << 5);
+    /// Endgame king centralization
+    pub const KING_ACTIVITY: Self = Self(1 << 6);
+    /// Mop-up: driving a lone king to the edge
+    pub const ENDGAME_KING: Self = Self(1 << 7);
+    pub const PASSED_PAWNS: Self = Self(1 << 8);
+    /// Penalty for standing in check
+    pub const CHECK: Self = Self(1 << 9);
+    /// Geodesic mobility difference
+    pub const MOBILITY: Self = Self(1 << 10);
+    pub const ALL: Self = Self(0b111_1111_1111);
+    pub const NONE: Self = Self(0);
+
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn insert(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+
+    pub fn remove(&mut self, other: Self) {
+        self.0 &= !other.0;
+    }
+}
+
+impl Default for EvalFlags {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+impl std::ops::BitOr for EvalFlags {
+    type Output = Self;
+    fn bitor(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+/// Calls to `calculate_cga_vision` on this thread, so tests can tell a skipped
+/// component from one that happened to score 0.
+#[cfg(test)]
+thread_local! {
+    static VISION_CALLS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
 pub struct GeotensorEvaluator {
+    /// Engine aggression: the MPS entanglement entropy (tactical tension, never
+    /// negative) scaled by this is credited to `tension_side`. Positive seeks tense,
+    /// tactical positions; negative steers toward quiet, drawish ones; 0 ignores
+    /// tension. Entropy is a property of the position, not of either side's mobility.
     pub tension_weight: f32,
-    pub tensors: [SquareTensor; 64],
+    /// Side whose preference `tension_weight` expresses, normally the engine's
+    /// color at the root. `None` credits whoever is to move in the evaluated
+    /// position, so the preference flips with search depth parity.
+    pub tension_side: Option<Color>,
+    pub weights: EvalWeights,
+    /// Terms included in `evaluate` (all by default), for ablation runs.
+    pub enabled_components: EvalFlags,
+    /// One tensor per square, or only the a-d files (32) when `mirror_tied` is set.
+    /// After editing these in place, call `tensors_changed`.
+    pub tensors: Vec<SquareTensor>,
+    /// Mirror-tied mode: the e-h files reuse the tensor of their file-mirrored square.
+    pub mirror_tied: bool,
+    /// Average the MPS and entropy terms with those of the file-mirrored board,
+    /// so mirrored positions get the same terms. Set by `into_mirror_tied`:
+    /// SNAKE_PATH alternates its direction from row to row, so tied tensors
+    /// alone would read a mirrored board in a different order. Doubles the cost
+    /// of the contraction.
+    pub mirror_average: bool,
+    /// Skip the MPS and entropy terms where `is_quiet` fails, leaving material
+    /// plus vision: tactics there are the search's job, not the geometry's.
+    pub quiet_gate: bool,
+    /// Start the contraction from a side-to-move dependent boundary (see
+    /// `boundary_state`) so tensors can express tempo; off keeps the plain
+    /// `[1, 0, ...]` boundary of earlier networks.
+    pub tempo_boundary: bool,
+    /// Padded copy of `tensors` for the SIMD contraction, built on first use
+    #[cfg(feature = "simd")]
+    packed: once_cell::unsync::OnceCell<Vec<PackedTensor>>,
 }
 
 impl GeotensorEvaluator {
+    /// Deterministic heuristic init: identity bonds on empty squares and a flat
+    /// 0.5 diagonal for every piece (stand-ins until trained via DMRG).
     pub fn new(_: Option<&str>) -> Self {
-        // Initialize with default (random or basic heuristic) strategic weights
-        let mut tensors: Vec<SquareTensor> = Vec::with_capacity(64);
-        for _ in 0..64 {
-            let mut data = [[[0.0; 10]; 10]; 13];
-            // Identity-like bond for empty squares to allow flow
-            for c in 0..10 {
-                data[0][c][c] = 1.0; 
-            }
-            // Add slight strategic biases for pieces (normally trained via DMRG)
-            for p in 1..13 {
-                for c in 0..10 {
-                    data[p][c][c] = 0.5;
-                }
-            }
-            tensors.push(SquareTensor { data });
-        }
+        let tensors = (0..64).map(|_| default_tensor()).collect();
 
         Self { 
             tension_weight: 1.0,
-            tensors: tensors.try_into().unwrap_or_else(|_| panic!("Failed to init tensors")),
+            tension_side: None,
+            weights: EvalWeights::default(),
+            enabled_components: EvalFlags::ALL,
+            tensors,
+            mirror_tied: false,
+            mirror_average: false,
+            quiet_gate: true,
+            tempo_boundary: false,
+            #[cfg(feature = "simd")]
+            packed: Default::default(),
+        }
+    }
+
+    /// The heuristic init of `new` plus uniform noise in [-0.05, 0.05) drawn from
+    /// a seeded xorshift, so randomized experiments are reproducible.
+    pub fn new_seeded(seed: u64) -> Self {
+        Self::new_with_noise(&mut XorShift64::new(seed))
+    }
+
+    /// `new_seeded` with the noise drawn from `rng`.
+    pub fn new_with_noise(rng: &mut (impl RngSource + ?Sized)) -> Self {
+        let mut evaluator = Self::new(None);
+        for tensor in evaluator.tensors.iter_mut() {
+            for v in tensor.data.iter_mut().flatten().flatten() {
+                *v += (rng.next_f32() - 0.5) * 0.1;
+            }
+        }
+        evaluator.tensors_changed();
+        evaluator
+    }
+
+    /// Wraps tensors produced elsewhere (tests, training loops) with default weights.
+    pub fn from_tensors(tensors: [SquareTensor; 64], tension_weight: f32) -> Self {
+        Self {
+            tension_weight,
+            tension_side: None,
+            weights: EvalWeights::default(),
+            enabled_components: EvalFlags::ALL,
+            tensors: tensors.into(),
+            mirror_tied: false,
+            mirror_average: false,
+            quiet_gate: true,
+            tempo_boundary: false,
+            #[cfg(feature = "simd")]
+            packed: Default::default(),
+        }
+    }
+
+    /// Fallible counterpart of `from_tensors` for tensors loaded at runtime: 64
+    /// tensors give a plain evaluator, 32 a mirror-tied one (a-d files, rank-major).
+    pub fn try_from_tensors(tensors: Vec<SquareTensor>, tension_weight: f32) -> Result<Self, GeoLlamaError> {
+        let mirror_tied = match tensors.len() {
+            64 => false,
+            32 => true,
+            n => return Err(EvalInitError::WrongTensorCount(n).into()),
+        };
+        for (t, tensor) in tensors.iter().enumerate() {
+            if let Some(index) = tensor.data.iter().flatten().flatten().position(|v| !v.is_finite()) {
+                return Err(EvalInitError::NonFiniteValue { tensor: t, index }.into());
+            }
+        }
+        Ok(Self {
+            tension_weight,
+            tension_side: None,
+            weights: EvalWeights::default(),
+            enabled_components: EvalFlags::ALL,
+            tensors,
+            mirror_tied,
+            mirror_average: mirror_tied,
+            quiet_gate: true,
+            tempo_boundary: false,
+            #[cfg(feature = "simd")]
+            packed: Default::default(),
+        })
+    }
+
+    /// Builds an evaluator from concatenated flat tensors (see `SquareTensor::from_slice`).
+    pub fn from_flat(values: &[f32], tension_weight: f32) -> Result<Self, GeoLlamaError> {
+        if !values.len().is_multiple_of(SquareTensor::LEN) {
+            return Err(EvalInitError::RaggedTensorData(values.len()).into());
+        }
+        let tensors = values
+            .chunks_exact(SquareTensor::LEN)
+            .map(|chunk| SquareTensor::from_slice(chunk).expect("chunk has LEN values"))
+            .collect();
+        Self::try_from_tensors(tensors, tension_weight)
+    }
+
+    /// Reads a tensor file: little-endian f32 values in `from_flat` order, with
+    /// no header. Rejects partial values, partial tensors, a tensor count other
+    /// than 64 or 32, and non-finite values.
+    pub fn load(path: impl AsRef<std::path::Path>, tension_weight: f32) -> Result<Self, GeoLlamaError> {
+        let bytes = std::fs::read(path).map_err(|e| EvalInitError::Io(e.kind()))?;
+        if bytes.len() % 4 != 0 {
+            return Err(EvalInitError::PartialValue(bytes.len()).into());
+        }
+        let values: Vec<f32> = bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        Self::from_flat(&values, tension_weight)
+    }
+
+    /// Replaces the tensors with ones whose contraction follows `classical_pst`,
+    /// for sensible play before any DMRG training. Bond 0 carries the amplitude
+    /// and bond 1 the running table sum, each site adding its bias times
+    /// `WARM_START_SCALE`: normalization keeps their ratio, so before the last
+    /// site the state is proportional to (1, S). The last site on the snake path
+    /// reads it out as (S, 1), making the final amplitude S / sqrt(1 + S^2).
+    /// The MPS term is therefore monotonic in the table sum and close to
+    /// linear for modest sums, but it saturates, and it stays bounded by
+    /// `mps_scale`. Like every contraction it sees absolute colors, so the
+    /// table is encoded from White's point of view. Leaves mirror-tied storage,
+    /// since the readout site differs from its mirror. The entanglement entropy
+    /// of these tensors only tracks the size of the running sum, not tension,
+    /// so `tension_weight` is set to 0.
+    pub fn warm_start(&mut self) {
+        self.load_running_sum(|p_idx, sq| {
+            WARM_START_SCALE * match p_idx {
+                0 => 0.0,
+                1..=6 => classical_pst(PIECE_ORDER[p_idx - 1], sq),
+                _ => -classical_pst(PIECE_ORDER[p_idx - 7], sq ^ 56),
+            }
+        });
+    }
+
+    /// Like `warm_start`, but the running sum is the material balance from
+    /// White's point of view: each piece adds its entry of `piece_values`
+    /// (pawn to king, centipawns) times `MATERIAL_BIAS_SCALE`, negated for
+    /// Black. A single contraction then yields a material-aware score that is
+    /// close to linear in the balance for ordinary imbalances and saturates
+    /// for lopsided ones.
+    pub fn with_material_bias(&mut self, piece_values: &[i32; 6]) {
+        self.load_running_sum(|p_idx, _| {
+            MATERIAL_BIAS_SCALE * match p_idx {
+                0 => 0.0,
+                1..=6 => piece_values[p_idx - 1] as f32,
+                _ => -piece_values[p_idx - 7] as f32,
+            }
+        });
+    }
+
+    /// Installs the two-bond running-sum tensors of `warm_start`, site `sq`
+    /// adding `bias(p_idx, sq)` for the physical index standing on it.
+    fn load_running_sum(&mut self, bias: impl Fn(usize, usize) -> f32) {
+        let last = SNAKE_PATH[63];
+        self.tensors = (0..64)
+            .map(|sq| {
+                let mut data = [[[0.0; CHI]; CHI]; PHYSICAL_DIM];
+                for (p_idx, slice) in data.iter_mut().enumerate() {
+                    let bias = bias(p_idx, sq);
+                    if sq == last {
+                        slice[0][0] = bias;
+                        slice[1][0] = 1.0;
+                        slice[0][1] = 1.0;
+                    } else {
+                        slice[0][0] = 1.0;
+                        slice[0][1] = bias;
+                        slice[1][1] = 1.0;
+                    }
+                }
+                SquareTensor { data }
+            })
+            .collect();
+        self.mirror_tied = false;
+        self.mirror_average = false;
+        self.tension_weight = 0.0;
+        self.tensors_changed();
+    }
+
+    /// Converts to mirror-tied storage, keeping the a-d file tensors (32 instead of 64),
+    /// with `mirror_average` on so file-mirrored positions evaluate alike.
+    /// Evaluates exactly like an untied evaluator with `mirror_average` whose
+    /// e-h file tensors are copies of their mirror squares'.
+    pub fn into_mirror_tied(mut self) -> Self {
+        if self.mirror_tied {
+            return self;
+        }
+        let tensors = std::mem::take(&mut self.tensors);
+        self.tensors = tensors
+            .into_iter()
+            .enumerate()
+            .filter(|(sq, _)| sq % 8 < 4)
+            .map(|(_, t)| t)
+            .collect();
+        self.mirror_tied = true;
+        self.mirror_average = true;
+        self.tensors_changed();
+        self
+    }
+
+    /// Drops data derived from `tensors` (the SIMD packed layout); call after
+    /// modifying them in place. A no-op without the `simd` feature.
+    pub fn tensors_changed(&mut self) {
+        #[cfg(feature = "simd")]
+        {
+            self.packed = Default::default();
+        }
+    }
+
+    /// Tensor used for a square, resolving mirror-tied storage.
+    pub fn tensor(&self, sq: usize) -> &SquareTensor {
+        if self.mirror_tied {
+            let sq = if sq % 8 < 4 { sq } else { mirror_square(sq) };
+            &self.tensors[(sq / 8) * 4 + sq % 8]
+        } else {
+            &self.tensors[sq]
         }
     }
 
     pub fn evaluate(&mut self, board: &Board) -> i32 {
+        self.evaluate_with_material(board, Material::from_board(board))
+    }
+
+    /// `evaluate` plus the geodesic terms of a propagated field: interference
+    /// tension around our pieces, king tropism and piece activity, each behind
+    /// its weight.
+    pub fn evaluate_with_field(&mut self, board: &Board, field: &GeodesicField) -> i32 {
+        let base = self.evaluate(board);
+        let flags = self.enabled_components;
+        let mut geodesic = 0.0;
+        if flags.contains(EvalFlags::ACTIVITY) && self.weights.piece_activity != 0.0 {
+            geodesic += self.piece_activity_score(board, field);
+        }
+        if !flags.contains(EvalFlags::GEODESIC) {
+            return base + geodesic as i32;
+        }
+        if self.weights.interference_tension != 0.0 {
+            let ours: Vec<usize> = board.colors(board.side_to_move()).into_iter().map(|sq| sq as usize).collect();
+            geodesic += self.weights.interference_tension * field.interference_tension(&ours);
+        }
+        if self.weights.king_tropism != 0.0 {
+            geodesic += self.king_tropism(board, field);
+        }
+        base + geodesic as i32
+    }
+
+    /// King tropism from the retrocausal wave: our attackers (minor and heavy
+    /// pieces) earn 1 / (1 + d) for their path length d in `field.retro_potentials`,
+    /// which must have been propagated from the enemy king; enemy attackers near
+    /// our own king cost the same. Scaled by `weights.king_tropism`.
+    pub fn king_tropism(&self, board: &Board, field: &GeodesicField) -> f32 {
         let us = board.side_to_move();
-        
+        let attackers = board.occupied() ^ board.pieces(Piece::Pawn) ^ board.pieces(Piece::King);
+        // The cost of entering the attacker's own square is left out, so the
+        // distance counts only the path between it and the king
+        let closeness = |dists: &[f32; 64], sq: usize| {
+            let d = dists[sq];
+            if d < f32::MAX { 1.0 / (1.0 + (d - field.costs[sq]).max(0.0)) } else { 0.0 }
+        };
+
+        let mut score = 0.0;
+        for sq in attackers & board.colors(us) {
+            score += closeness(&field.retro_potentials, sq as usize);
+        }
+        let our_king = field.distances_from(board.king(us) as usize, None, board);
+        for sq in attackers & board.colors(!us) {
+            score -= closeness(&our_king, sq as usize);
+        }
+        self.weights.king_tropism * score
+    }
+
+    /// Geodesic mobility: per piece, the squares it reaches within
+    /// `MOBILITY_RADIUS` plain steps of field cost (scaled by its step cost, so
+    /// one knight hop counts like one king step), weighted by `MOBILITY_WEIGHTS`;
+    /// ours minus the opponent's, scaled by `weights.mobility`. Squares our own
+    /// pieces stand on or that sit against the enemy pawn chain are expensive
+    /// in the field, so they drop out. 0 in check, where only evasions matter.
+    pub fn mobility_score(&self, board: &Board) -> f32 {
+        let passed = match board.null_move() {
+            Some(passed) => passed,
+            None => return 0.0,
+        };
+        self.weights.mobility * (side_mobility(board) - side_mobility(&passed))
+    }
+
+    /// `evaluate` with a material balance maintained incrementally by the caller.
+    pub fn evaluate_with_material(&mut self, board: &Board, material: Material) -> i32 {
+        self.breakdown_with_material(board, material).total
+    }
+
+    /// Per-component view of `evaluate`, side-to-move relative.
+    pub fn breakdown(&mut self, board: &Board) -> EvalBreakdown {
+        self.breakdown_with_material(board, Material::from_board(board))
+    }
+
+    /// Per-component view of `evaluate_with_material`; disabled components are 0.
+    pub fn breakdown_with_material(&mut self, board: &Board, material: Material) -> EvalBreakdown {
+        let mut parts = EvalBreakdown::default();
+        if is_insufficient_material(board) {
+            return parts;
+        }
+        let us = board.side_to_move();
+        let flags = self.enabled_components;
+
         // 1. Classical Baseline
-        let mut score = (self.material_score(board, us) - self.material_score(board, !us)) as f32;
+        if flags.contains(EvalFlags::MATERIAL) {
+            parts.material = material.relative(us) as f32;
+        }
+
+        // Sparse endgames are about calculation, not geometry
+        let geometric = self.geometric_scale(board);
+        if flags.contains(EvalFlags::KING_ACTIVITY) && geometric < 1.0 {
+            parts.king_activity = (1.0 - geometric) * self.weights.king_activity * king_activity(board, us);
+        }
+        if flags.contains(EvalFlags::ENDGAME_KING) {
+            parts.endgame_king = endgame_king_term(board) as f32;
+        }
+        if flags.contains(EvalFlags::PASSED_PAWNS) && self.weights.passed_pawn != 0.0 {
+            parts.passed_pawns = self.weights.passed_pawn * passed_pawn_score(board) as f32;
+        }
+
+        // A checked king is exposed, whatever the horizon makes of it
+        let in_check = !board.checkers().is_empty();
+        if flags.contains(EvalFlags::CHECK) && in_check {
+            parts.check = -self.weights.check_penalty * check_severity(board);
+        }
 
         // 2. Geometric Vision (CGA Blades)
-        score += self.calculate_cga_vision(board);
+        if flags.contains(EvalFlags::VISION) {
+            let scale = if in_check { geometric * self.weights.check_vision_scale } else { geometric };
+            parts.vision = scale * self.calculate_cga_vision(board);
+            parts.knight_forks = scale * self.knight_fork_score(board);
+        }
 
         // 3. Tensor Network Contraction & Entropy
-        let (mps_val, entropy) = self.evaluate_mps_with_entropy(board);
-        score += mps_val;
-        
-        // High entropy (tactical tension) favors the side with better mobility
-        score += entropy * self.tension_weight;
+        let tensor_terms = flags.contains(EvalFlags::MPS) || flags.contains(EvalFlags::ENTROPY);
+        if tensor_terms && (!self.quiet_gate || is_quiet(board)) {
+            let (mps_val, entropy) = self.evaluate_mps_with_entropy(board);
+            if flags.contains(EvalFlags::MPS) {
+                parts.mps = geometric * mps_val;
+            }
+            // Signed by tension_weight: positive rewards tension, negative avoids it
+            if flags.contains(EvalFlags::ENTROPY) {
+                let tension = geometric * entropy * self.tension_weight;
+                parts.entropy = match self.tension_side {
+                    Some(side) if side != us => -tension,
+                    _ => tension,
+                };
+            }
+        }
 
-        score as i32
+        if flags.contains(EvalFlags::MOBILITY) && self.weights.mobility != 0.0 {
+            parts.mobility = self.mobility_score(board);
+        }
+
+        parts.total = (parts.material
+            + parts.vision
+            + parts.knight_forks
+            + parts.mps
+            + parts.entropy
+            + parts.king_activity
+            + parts.check
+            + parts.mobility
+            + parts.endgame_king
+            + parts.passed_pawns) as i32;
+        parts
+    }
+
+    /// Weight of the geometric terms: 1 from `weights.endgame_pieces` pieces up,
+    /// falling linearly to 0 with only the kings left.
+    pub fn geometric_scale(&self, board: &Board) -> f32 {
+        let pieces = board.occupied().len();
+        let threshold = self.weights.endgame_pieces;
+        if pieces >= threshold {
+            return 1.0;
+        }
+        ((pieces as f32 - 2.0) / (threshold as f32 - 2.0).max(1.0)).clamp(0.0, 1.0)
     }
 
+    /// Sweeps the SNAKE_PATH and truncates the bond between each adjacent pair of
+    /// tensors via SVD, keeping at most `max_chi` singular values and dropping the
+    /// smallest ones while their relative weight (sum of s^2 / total) stays within
+    /// `tolerance`. The factors are folded back (left-canonical A, S*V^T into B), so
+    /// an untruncated bond leaves the contracted MPS value unchanged; only the
+    /// per-step entropy sees the gauge change. Since every contraction step
+    /// renormalizes, S is stored relative to its largest value to keep the swept
+    /// tensors at unit scale. Returns the total discarded weight, or None for
+    /// mirror-tied tensors: they are shared between squares, so the sweep
+    /// cannot refactor them pairwise, and they are left untouched.
+    pub fn truncate_bonds(&mut self, max_chi: usize, tolerance: f32) -> Option<f32> {
+        if self.mirror_tied {
+            return None;
+        }
+        let max_chi = max_chi.clamp(1, CHI);
+        let tolerance = tolerance.max(0.0) as f64;
+        let rows = PHYSICAL_DIM * CHI;
+        let mut discarded = 0.0f64;
+
+        for pair in SNAKE_PATH.windows(2) {
+            let (left, right) = (pair[0], pair[1]);
+
+            // A reshaped to (phys, left) x bond, and B^T to (phys, right) x bond.
+            let mut qa = vec![[0.0f64; CHI]; rows];
+            let mut qb = vec![[0.0f64; CHI]; rows];
+            for p in 0..PHYSICAL_DIM {
+                for i in 0..CHI {
+                    for m in 0..CHI {
+                        qa[p * CHI + i][m] = self.tensors[left].data[p][i][m] as f64;
+                        qb[p * CHI + i][m] = self.tensors[right].data[p][m][i] as f64;
+                    }
+                }
+            }
+            let ra = linalg::qr_in_place(&mut qa);
+            let rb = linalg::qr_in_place(&mut qb);
+
+            // A * B = Qa * (Ra * Rb^T) * Qb^T, so only the small core needs an SVD.
+            let mut core = [[0.0f64; CHI]; CHI];
+            for i in 0..CHI {
+                for j in 0..CHI {
+                    core[i][j] = (0..CHI).map(|m| ra[i][m] * rb[j][m]).sum();
+                }
+            }
+            let (u, sv, v) = linalg::svd(&core);
+
+            let total: f64 = sv.iter().map(|x| x * x).sum();
+            if total <= 0.0 {
+                continue;
+            }
+            let mut keep = CHI;
+            let mut tail = 0.0;
+            while keep > 1 {
+                let w = sv[keep - 1] * sv[keep - 1] / total;
+                if keep > max_chi || tail + w <= tolerance {
+                    tail += w;
+                    keep -= 1;
+                } else {
+                    break;
+                }
+            }
+            discarded += tail;
+
+            for p in 0..PHYSICAL_DIM {
+                for i in 0..CHI {
+                    for k in 0..CHI {
+                        let (a_val, b_val) = if k < keep {
+                            let a: f64 = (0..CHI).map(|j| qa[p * CHI + i][j] * u[j][k]).sum();
+                            let b: f64 = (0..CHI).map(|j| qb[p * CHI + i][j] * v[j][k]).sum();
+                            (a, sv[k] / sv[0] * b)
+                        } else {
+                            (0.0, 0.0)
+                        };
+                        self.tensors[left].data[p][i][k] = a_val as f32;
+                        self.tensors[right].data[p][k][i] = b_val as f32;
+                    }
+                }
+            }
+        }
+        self.tensors_changed();
+        Some(discarded as f32)
+    }
+
+    /// Rewards infiltrating pieces: each piece earns a bonus inversely
+    /// proportional to its geodesic distance to the enemy king, measured with
+    /// that piece type's own mobility (knight wormholes, slider rays); ours
+    /// minus the opponent's. Runs one search per piece type and side, so it
+    /// is only blended in by `evaluate_with_field`.
+    pub fn piece_activity_score(&self, board: &Board, field: &GeodesicField) -> f32 {
+        let us = board.side_to_move();
+        self.weights.piece_activity * (side_activity(board, field, us) - side_activity(board, field, !us))
+    }
+
+    /// Knights don't fit the blade model, so their double attacks are scored
+    /// separately: each fork is worth the second most valuable victim.
+    fn knight_fork_score(&self, board: &Board) -> f32 {
+        let us = board.side_to_move();
+        let mut score = 0.0;
+        for color in [us, !us] {
+            let sign = if color == us { 1.0 } else { -1.0 };
+            for (_, victims) in knight_forks(board, color) {
+                let mut values: Vec<i32> = victims.iter().map(|&v| fork_victim_value(board.piece_on(v).unwrap())).collect();
+                values.sort_unstable_by(|a, b| b.cmp(a));
+                // Forking pawns is noise; only forks winning at least a minor count
+                if values[1] >= piece_value(Piece::Knight) {
+                    score += sign * self.weights.knight_fork * values[1] as f32;
+                }
+            }
+        }
+        score
+    }
+
+    /// Mirror-tied tensors are resolved per square by `tensor`, so tied and
+    /// untied evaluators share the contraction along SNAKE_PATH. With
+    /// `mirror_average` the file-mirrored board is contracted too and both
+    /// terms are averaged, which is the same pair of contractions for the
+    /// mirrored position.
     fn evaluate_mps_with_entropy(&self, board: &Board) -> (f32, f32) {
-        let mut state = [0.0; 10];
-        state[0] = 1.0;
+        let boundary = self.boundary_state(board);
+        let sites = site_indices(board);
+        let (mps_val, entropy) = self.mps_terms(&sites, &boundary);
+        if !self.mirror_average {
+            return (mps_val, entropy);
+        }
+        let mirrored = std::array::from_fn(|sq| sites[mirror_square(sq)]);
+        let (mirror_val, mirror_entropy) = self.mps_terms(&mirrored, &boundary);
+        (0.5 * (mps_val + mirror_val), 0.5 * (entropy + mirror_entropy))
+    }
+
+    fn mps_terms(&self, sites: &[usize; 64], boundary: &[f32; CHI]) -> (f32, f32) {
+        let (state, entropy) = self.contract_sites(sites, boundary);
+
+        // The final state is unit-norm, so its boundary amplitude lies in [-1, 1].
+        // tanh(2a) spreads that over ~96% of (-1, 1), and `mps_scale` bounds the
+        // term in centipawns so retrained tensors can't swamp material.
+        let mps_val = self.weights.mps_scale * (2.0 * state[0]).tanh();
+        (mps_val, entropy)
+    }
+
+    /// Left boundary of the contraction: bond 0, or with `tempo_boundary`
+    /// (e0 + e_last) / sqrt(2) for Black to move and (e0 - e_last) / sqrt(2)
+    /// for White, the last bond channel carrying the tempo bit. Tensors that
+    /// never feed that channel into the others evaluate the same either way.
+    pub fn boundary_state(&self, board: &Board) -> [f32; CHI] {
+        let mut state = [0.0; CHI];
+        if self.tempo_boundary {
+            let tempo = match board.side_to_move() {
+                Color::White => -1.0,
+                Color::Black => 1.0,
+            };
+            state[0] = std::f32::consts::FRAC_1_SQRT_2;
+            state[CHI - 1] = tempo * std::f32::consts::FRAC_1_SQRT_2;
+        } else {
+            state[0] = 1.0;
+        }
+        state
+    }
+
+    /// Contracts the MPS along `SNAKE_PATH` starting from `initial_state`
+    /// (its first CHI values, zero-padded) instead of the `[1, 0, ...]`
+    /// boundary, for experiments with boundary conditions or for composing
+    /// environments. Returns the final state and the scaled entanglement
+    /// entropy. The state is renormalized after every site, so the result is
+    /// unit-norm; if it collapses it restarts from `initial_state`, and the
+    /// entropy is then reported as 0.
+    pub fn contract_mps(&self, board: &Board, initial_state: &[f32]) -> ([f32; CHI], f32) {
+        let mut initial = [0.0; CHI];
+        for (v, &x) in initial.iter_mut().zip(initial_state) {
+            *v = x;
+        }
+        self.contract_sites(&site_indices(board), &initial)
+    }
+
+    /// `contract_mps` over physical indices given per square (`site_indices`).
+    fn contract_sites(&self, sites: &[usize; 64], initial: &[f32; CHI]) -> ([f32; CHI], f32) {
+        let initial = *initial;
+        let mut state = initial;
         let mut total_entropy = 0.0;
+        let mut degenerate = false;
 
         for &sq_idx in SNAKE_PATH.iter() {
-            let p_idx = get_piece_index(board, Square::index(sq_idx));
-            let square_tensor = &self.tensors[sq_idx].data[p_idx];
-            
-            let mut next_state = [0.0; 10];
+            let p_idx = sites[sq_idx];
+            let mut next_state = self.contract_site(&state, sq_idx, p_idx);
             let mut norm = 0.0;
-            for (curr_bond, &val) in state.iter().enumerate() {
-                if val == 0.0 { continue; }
-                for next_bond in 0..10 {
-                    next_state[next_bond] += val * square_tensor[curr_bond][next_bond];
-                }
-            }
             
             // Normalize and calculate Local Von Neumann Entropy
             for v in next_state.iter() { norm += v * v; }
-            norm = norm.sqrt().max(1e-9);
+            norm = norm.sqrt();
+            if norm.is_nan() || norm <= MPS_DEGENERATE_NORM {
+                // Collapsed (or NaN) state: restart from the boundary vector
+                degenerate = true;
+                state = initial;
+                continue;
+            }
             for v in next_state.iter_mut() {
                 *v /= norm;
                 if *v > 0.0 {
@@ -88,98 +928,635 @@ impl GeotensorEvaluator {
             state = next_state;
         }
 
-        (state[0] * 100.0, total_entropy * 10.0)
+        // The entropy of a contraction that had to be restarted is meaningless
+        if degenerate {
+            return (state, 0.0);
+        }
+        (state, total_entropy * 10.0)
+    }
+
+    /// `state` times square `sq`'s matrix for physical index `p_idx`.
+    #[cfg(not(feature = "simd"))]
+    #[inline(always)]
+    fn contract_site(&self, state: &[f32; CHI], sq: usize, p_idx: usize) -> [f32; CHI] {
+        let square_tensor = &self.tensor(sq).data[p_idx];
+        let mut next_state = [0.0; CHI];
+        for (curr_bond, &val) in state.iter().enumerate() {
+            if val == 0.0 { continue; }
+            for next_bond in 0..CHI {
+                next_state[next_bond] += val * square_tensor[curr_bond][next_bond];
+            }
+        }
+        next_state
+    }
+
+    /// `state` times square `sq`'s matrix for physical index `p_idx`, over rows
+    /// padded to whole vector registers. Same operations in the same order as
+    /// the plain layout, so results are bit-identical.
+    #[cfg(feature = "simd")]
+    #[inline(always)]
+    fn contract_site(&self, state: &[f32; CHI], sq: usize, p_idx: usize) -> [f32; CHI] {
+        let rows = &self.packed()[sq].rows[p_idx];
+        let mut acc = [0.0; PACKED_LANES];
+        for (row, &val) in rows.iter().zip(state.iter()) {
+            if val == 0.0 { continue; }
+            for (a, &t) in acc.iter_mut().zip(row.iter()) {
+                *a += val * t;
+            }
+        }
+        let mut next_state = [0.0; CHI];
+        next_state.copy_from_slice(&acc[..CHI]);
+        next_state
+    }
+
+    #[cfg(feature = "simd")]
+    fn packed(&self) -> &[PackedTensor] {
+        self.packed.get_or_init(|| (0..64).map(|sq| PackedTensor::new(self.tensor(sq))).collect())
+    }
+
+    /// Partial contractions at every cut of SNAKE_PATH: `left[k]` has absorbed the
+    /// first k tensors from the left boundary (`boundary_state`), `right[k]` the
+    /// tensors k.. from the right boundary (bond 0). Each vector is unit-normalized
+    /// like the evaluator's state, so `left[k] · right[k]` equals the full boundary
+    /// amplitude up to a positive per-cut scale, and exactly at k = 64.
+    pub fn mps_environments(&self, board: &Board) -> (Vec<[f32; CHI]>, Vec<[f32; CHI]>) {
+        let normalize = |v: &mut [f32; CHI]| {
+            let norm = v.iter().map(|x| x * x).sum::<f32>().sqrt();
+            if norm.is_nan() || norm <= MPS_DEGENERATE_NORM {
+                *v = [0.0; CHI];
+                v[0] = 1.0;
+                return;
+            }
+            for x in v.iter_mut() {
+                *x /= norm;
+            }
+        };
+        let site = |k: usize| &self.tensor(SNAKE_PATH[k]).data[get_piece_index(board, Square::index(SNAKE_PATH[k]))];
+
+        let mut left = Vec::with_capacity(SNAKE_PATH.len() + 1);
+        let mut state = self.boundary_state(board);
+        left.push(state);
+        for k in 0..SNAKE_PATH.len() {
+            let t = site(k);
+            let mut next = [0.0; CHI];
+            for (l, &val) in state.iter().enumerate() {
+                for (r, out) in next.iter_mut().enumerate() {
+                    *out += val * t[l][r];
+                }
+            }
+            normalize(&mut next);
+            state = next;
+            left.push(state);
+        }
+
+        let mut right = vec![[0.0; CHI]; SNAKE_PATH.len() + 1];
+        let mut state = [0.0; CHI];
+        state[0] = 1.0;
+        right[SNAKE_PATH.len()] = state;
+        for k in (0..SNAKE_PATH.len()).rev() {
+            let t = site(k);
+            let mut next = [0.0; CHI];
+            for (l, out) in next.iter_mut().enumerate() {
+                *out = t[l].iter().zip(state.iter()).map(|(a, b)| a * b).sum();
+            }
+            normalize(&mut next);
+            state = next;
+            right[k] = state;
+        }
+        (left, right)
+    }
+
+    /// Bond entropy at each of the 63 interior cuts of SNAKE_PATH (cut k lies
+    /// between path sites k and k + 1): the Shannon entropy of how the boundary
+    /// amplitude `left · right` splits over the bond channels, p_m ∝
+    /// (left[m] right[m])^2. 0 where a single channel carries everything, up to
+    /// ln(CHI) when all share it evenly. A profile that is zero everywhere means
+    /// the network has collapsed to a product state (bond dimension 1).
+    pub fn entropy_profile(&self, board: &Board) -> [f32; 63] {
+        let (left, right) = self.mps_environments(board);
+        let mut profile = [0.0; 63];
+        for (k, entropy) in profile.iter_mut().enumerate() {
+            let weights: Vec<f32> = left[k + 1].iter().zip(right[k + 1].iter()).map(|(l, r)| (l * r) * (l * r)).collect();
+            let total: f32 = weights.iter().sum();
+            if total > 0.0 {
+                *entropy = weights.iter().filter(|&&w| w > 0.0).map(|&w| w / total).fold(0.0, |h, p| h - p * p.ln());
+            }
+        }
+        profile
+    }
+
+    /// Per-square view of the MPS: |left[k] · T_k · right[k+1]|, the amplitude of
+    /// square k's tensor seen through its unit-normalized environments. Indexed by
+    /// square (a1 = 0), not by SNAKE_PATH position.
+    pub fn mps_site_amplitudes(&self, board: &Board) -> [f32; 64] {
+        let (left, right) = self.mps_environments(board);
+        let mut out = [0.0; 64];
+        for (k, &sq) in SNAKE_PATH.iter().enumerate() {
+            let t = &self.tensor(sq).data[get_piece_index(board, Square::index(sq))];
+            let mut amp = 0.0;
+            for (l, row) in t.iter().enumerate() {
+                amp += left[k][l] * row.iter().zip(right[k + 1].iter()).map(|(a, b)| a * b).sum::<f32>();
+            }
+            out[sq] = amp.abs();
+        }
+        out
+    }
+
+    /// Each slider's vision term on its own square (signed for the side to move,
+    /// zero elsewhere); sums to the vision component of the eval.
+    pub fn vision_by_square(&self, board: &Board) -> [f32; 64] {
+        let us = board.side_to_move();
+        let sliders = sliders(board);
+        let occupants = occupants(board);
+        let mut out = [0.0; 64];
+        for sq in sliders {
+            out[sq as usize] = self.signed_slider_vision(board, &occupants, sq, us);
+        }
+        out
     }
 
     fn calculate_cga_vision(&self, board: &Board) -> f32 {
+        #[cfg(test)]
+        VISION_CALLS.with(|calls| calls.set(calls.get() + 1));
         let us = board.side_to_move();
-        let mut vision_score = 0.0;
-        let occupied = board.occupied();
-
-        for sq in occupied {
-            let sq_idx = sq as usize;
-            let p_vec = BOARD_SPACE[sq_idx];
-            let piece = board.piece_on(sq).unwrap();
-            let color = board.color_on(sq).unwrap();
-            
-            // Define the blade (Line or Plane at infinity)
-            let blade = match piece {
-                Piece::Rook => Some(crate::cga::Multivector5D::rook_blade(&p_vec)),
-                Piece::Bishop => Some(crate::cga::Multivector5D::bishop_blade(&p_vec)),
-                Piece::Queen => Some(crate::cga::Multivector5D::rook_blade(&p_vec) + crate::cga::Multivector5D::bishop_blade(&p_vec)),
-                _ => None,
-            };
-            
-            if let Some(b) = blade {
-                // Collect all targets on this blade line
-                let mut targets = Vec::with_capacity(8);
-                
-                for other_sq in occupied {
-                    if other_sq == sq { continue; }
-                    let other_p = BOARD_SPACE[other_sq as usize];
-                    // Geometric Product Check: If P lies on L, then P ^ L = 0 (or inner product in dual space)
-                    // Here we verify intersection using the inner product with the blade
-                    let intersection = b.inner_product(&other_p).abs();
-                    
-                    if intersection < 0.01 {
-                        // It's on the line. Calculate Euclidean distance for sorting.
-                        let dist = (sq.rank() as i32 - other_sq.rank() as i32).pow(2) + 
-                                   (sq.file() as i32 - other_sq.file() as i32).pow(2);
-                        targets.push((dist, other_sq));
-                    }
+        let sliders = sliders(board);
+        let occupants = occupants(board);
+        sliders.into_iter().map(|sq| self.signed_slider_vision(board, &occupants, sq, us)).sum()
+    }
+
+    /// Change in vision score caused by `mv`, measured from the mover's side:
+    /// the after-position's vision (now scored for the opponent) is
+    /// `-(vision_before + delta)`. Only sliders standing on, or with a line
+    /// through, a square whose contents change are recomputed.
+    pub fn cga_vision_delta(&self, board_before: &Board, mv: Move) -> f32 {
+        let us = board_before.side_to_move();
+        let mut after = board_before.clone();
+        after.play_unchecked(mv);
+
+        // Covers en passant victims and castling rooks, not just from/to
+        let changed = Piece::ALL
+            .iter()
+            .map(|&piece| board_before.pieces(piece) ^ after.pieces(piece))
+            .chain(Color::ALL.iter().map(|&color| board_before.colors(color) ^ after.colors(color)))
+            .fold(BitBoard::EMPTY, |acc, diff| acc | diff);
+
+        // A slider's own square lies on its lines, so this also catches sliders that moved
+        let affected = |board: &Board| {
+            let mut found = BitBoard::EMPTY;
+            for slider in sliders(board) {
+                let piece = board.piece_on(slider).unwrap();
+                if slider_lines(piece).iter().any(|&dir| !(line_squares(slider, dir) & changed).is_empty()) {
+                    found |= slider.bitboard();
                 }
+            }
+            found
+        };
 
-                // Sort by distance to simulate ray-casting
-                targets.sort_by_key(|k| k.0);
-
-                // Attenuation Loop (The "Wedge" logic)
-                let mut opacity = 1.0; 
-                for (_, target_sq) in targets {
-                    let target_piece = board.piece_on(target_sq).unwrap();
-                    let target_color = board.color_on(target_sq).unwrap();
-                    
-                    // Base value of hitting this square
-                    let value = match target_piece {
-                         Piece::Pawn => 1.0,
-                         Piece::Knight | Piece::Bishop => 3.0,
-                         Piece::Rook => 5.0,
-                         Piece::Queen => 9.0,
-                         Piece::King => 0.0, // Check logic handled elsewhere
-                    };
-
-                    if target_color == !us {
-                        // Impact: We hit an enemy. Add score weighted by remaining opacity.
-                        vision_score += if color == us { 5.0 * value * opacity } else { -5.0 * value * opacity };
-                        
-                        // Enemy pieces are solid walls
-                        opacity = 0.0; 
-                    } else {
-                        // We hit a friend (X-Ray defense). 
-                        vision_score += if color == us { 0.5 * value * opacity } else { -0.5 * value * opacity };
-                        
-                        // Friendly pieces are semi-transparent (Transparency = 0.2)
-                        opacity *= 0.2;
-                    }
+        let (occupants_before, occupants_after) = (occupants(board_before), occupants(&after));
+        let before: f32 = affected(board_before)
+            .into_iter()
+            .map(|sq| self.signed_slider_vision(board_before, &occupants_before, sq, us))
+            .sum();
+        let after_sum: f32 = affected(&after)
+            .into_iter()
+            .map(|sq| self.signed_slider_vision(&after, &occupants_after, sq, us))
+            .sum();
+        after_sum - before
+    }
+
+    /// One slider's vision term, signed for `us`. The slider's line blades pick
+    /// out the pieces it can see; each half-line is then walked as its own ray,
+    /// nearest piece first, so the term agrees with the ray-walking
+    /// `reference_vision` and depends on nothing off the slider's lines.
+    /// `occupants` is `occupants(board)`, built once per evaluation.
+    fn signed_slider_vision(&self, board: &Board, occupants: &Occupants, sq: Square, us: Color) -> f32 {
+        let (piece, color) = occupants[sq as usize].unwrap();
+        let bit = 1u64 << sq as u8;
+        let mut score = 0.0;
+        for &dir in slider_lines(piece) {
+            let line = (board.occupied() & line_squares(sq, dir)).0 & !bit;
+            // Square indices run monotonically along a line, so above the slider
+            // the nearest piece has the lowest index and below it the highest
+            score += self.ray_vision(occupants, sq, color, BitBoard(line & !(bit - 1)).into_iter());
+            score += self.ray_vision(occupants, sq, color, squares_descending(BitBoard(line & (bit - 1))));
+        }
+        if color == us { score } else { -score }
+    }
+
+    /// Vision of a `color` slider on `from` along one ray, unsigned. `ray`
+    /// yields the occupied squares nearest first.
+    fn ray_vision(&self, occupants: &Occupants, from: Square, color: Color, ray: impl Iterator<Item = Square>) -> f32 {
+        let w = &self.weights;
+        let mut score = 0.0;
+        let mut opacity = 1.0;
+        for target_sq in ray {
+            let (target_piece, target_color) = occupants[target_sq as usize].unwrap();
+            let dist = (from.rank() as i32 - target_sq.rank() as i32).pow(2)
+                + (from.file() as i32 - target_sq.file() as i32).pow(2);
+
+            // Base value of hitting this square, fading with distance
+            let value = vision_value(target_piece) * distance_falloff(w, dist);
+
+            if target_color != color {
+                // Impact: the slider hits an enemy, which is a solid wall
+                score += w.vision_hit * value * opacity;
+                break;
+            }
+            // We hit a friend (X-Ray defense). Friendly pieces are
+            // semi-transparent; batteries nearly clear
+            score += w.vision_xray * value * opacity;
+            opacity *= if extends_battery(target_piece, from, target_sq) {
+                w.battery_transparency
+            } else {
+                w.friendly_transparency
+            };
+            if opacity < w.opacity_cutoff { break; }
+        }
+        score
+    }
+}
+
+/// Squares of `bb` from the highest index down.
+fn squares_descending(bb: BitBoard) -> impl Iterator<Item = Square> {
+    let mut bits = bb.0;
+    std::iter::from_fn(move || {
+        if bits == 0 {
+            return None;
+        }
+        let i = 63 - bits.leading_zeros() as usize;
+        bits ^= 1 << i;
+        Some(Square::index(i))
+    })
+}
+
+/// Piece and color on each square, for loops that would otherwise call
+/// `piece_on` / `color_on` per square visited.
+type Occupants = [Option<(Piece, Color)>; 64];
+
+fn occupants(board: &Board) -> Occupants {
+    let mut table = [None; 64];
+    for color in Color::ALL {
+        for piece in Piece::ALL {
+            for sq in board.colored_pieces(color, piece) {
+                table[sq as usize] = Some((piece, color));
+            }
+        }
+    }
+    table
+}
+
+fn default_tensor() -> SquareTensor {
+    // Identity-like bond for empty squares to allow flow
+    let mut tensor = SquareTensor::identity();
+    // Add slight strategic biases for pieces (normally trained via DMRG)
+    for v in tensor.data.iter_mut().skip(1).flatten().flatten() {
+        *v *= 0.5;
+    }
+    tensor
+}
+
+/// Per-centipawn weight of `classical_pst` in the tensors built by `warm_start`
+const WARM_START_SCALE: f32 = 0.01;
+
+/// Per-centipawn weight of the piece values in `with_material_bias`: a queen
+/// up moves the running sum by 0.18, well inside the readout's linear range
+const MATERIAL_BIAS_SCALE: f32 = 0.0002;
+
+/// Pieces in physical-index order (after Empty), as in `get_piece_index`.
+const PIECE_ORDER: [Piece; 6] = [Piece::Pawn, Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen, Piece::King];
 
-                    if opacity < 0.05 { break; }
+/// Simple piece-square table in centipawns for a White piece on `sq`:
+/// advanced pawns, centralized minors and queen, and a king kept out of the
+/// center. Black uses the rank-mirrored square.
+pub fn classical_pst(piece: Piece, sq: usize) -> f32 {
+    let (file, rank) = ((sq % 8) as i32, (sq / 8) as i32);
+    // 0 on the rim up to 3 on the four center squares
+    let center = (3 - (3 - file).max(file - 4).max(3 - rank).max(rank - 4)) as f32;
+    match piece {
+        Piece::Pawn => 5.0 * (rank - 1).max(0) as f32,
+        Piece::Knight => 10.0 * center - 15.0,
+        Piece::Bishop => 5.0 * center - 5.0,
+        Piece::Rook => if rank == 6 { 10.0 } else { 0.0 },
+        Piece::Queen => 3.0 * center,
+        Piece::King => -10.0 * center,
+    }
+}
+
+/// Vision value of a piece struck by a blade (king hits are handled as checks elsewhere).
+fn vision_value(piece: Piece) -> f32 {
+    match piece {
+        Piece::Pawn => 1.0,
+        Piece::Knight | Piece::Bishop => 3.0,
+        Piece::Rook => 5.0,
+        Piece::Queen => 9.0,
+        Piece::King => 0.0,
+    }
+}
+
+/// Whether a friendly `piece` on `to` slides along the same line type as the
+/// ray from `from`, i.e. it forms a battery with the piece behind it.
+fn extends_battery(piece: Piece, from: Square, to: Square) -> bool {
+    let orthogonal = from.rank() == to.rank() || from.file() == to.file();
+    match piece {
+        Piece::Queen => true,
+        Piece::Rook => orthogonal,
+        Piece::Bishop => !orthogonal,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+const ROOK_DIRS: [(i8, i8); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+#[cfg(test)]
+const BISHOP_DIRS: [(i8, i8); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+#[cfg(test)]
+const QUEEN_DIRS: [(i8, i8); 8] = [(1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+/// Geodesic cost radius, in plain steps, of the squares counted as mobility
+const MOBILITY_RADIUS: f32 = 1.0;
+
+/// Mobility weight per reachable square, in `PIECE_ORDER`: minor pieces gain
+/// most from freedom, pawns and kings are left out.
+const MOBILITY_WEIGHTS: [f32; 6] = [0.0, 4.0, 3.0, 2.0, 1.0, 0.0];
+
+/// Unweighted-by-`EvalWeights` mobility of the side to move (see `mobility_score`).
+fn side_mobility(board: &Board) -> f32 {
+    let mut field = GeodesicField::new();
+    field.update_costs(board);
+    let ours = board.colors(board.side_to_move());
+    let mut total = 0.0;
+    for (&piece, weight) in PIECE_ORDER.iter().zip(MOBILITY_WEIGHTS) {
+        if weight == 0.0 {
+            continue;
+        }
+        let radius = MOBILITY_RADIUS * field.step_cost(Some(piece));
+        for sq in ours & board.pieces(piece) {
+            let dists = field.distances_from(sq as usize, Some(piece), board);
+            let reachable = dists.iter().filter(|&&d| d > 0.0 && d <= radius).count();
+            total += weight * reachable as f32;
+        }
+    }
+    total
+}
+
+/// How bad the check on the side to move is: 1 per checking piece, 2 for one
+/// adjacent to the king (it can't be blocked). 0 when not in check.
+pub fn check_severity(board: &Board) -> f32 {
+    let king = board.king(board.side_to_move());
+    board
+        .checkers()
+        .into_iter()
+        .map(|sq| if get_king_moves(king).has(sq) { 2.0 } else { 1.0 })
+        .sum()
+}
+
+/// Sum over `color`'s pieces of 1 / (1 + d), d being the piece's geodesic
+/// distance to the enemy king under its own mobility.
+fn side_activity(board: &Board, field: &GeodesicField, color: Color) -> f32 {
+    let enemy_king = board.king(!color) as usize;
+    let mut score = 0.0;
+    for piece in [Piece::Pawn, Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen] {
+        let pieces = board.colored_pieces(color, piece);
+        if pieces.is_empty() {
+            continue;
+        }
+        let dists = field.distances_from(enemy_king, Some(piece), board);
+        for sq in pieces {
+            let d = dists[sq as usize];
+            if d < f32::MAX {
+                score += 1.0 / (1.0 + d);
+            }
+        }
+    }
+    score
+}
+
+/// King activity for `us` minus the opponent's: each king earns its
+/// centralization (0 in the corner region to 3 in the center) plus its average
+/// closeness to the pawns of both sides (0 to 7), where endgames are decided.
+pub fn king_activity(board: &Board, us: Color) -> f32 {
+    let pawns = board.pieces(Piece::Pawn);
+    let activity = |king: Square| {
+        let (file, rank) = (king.file() as i32, king.rank() as i32);
+        let center = 3 - (3 - file).max(file - 4).max(3 - rank).max(rank - 4);
+        let mut closeness = 0.0;
+        for pawn in pawns {
+            let d = (pawn.file() as i32 - file).abs().max((pawn.rank() as i32 - rank).abs());
+            closeness += (7 - d) as f32;
+        }
+        center as f32 + if pawns.is_empty() { 0.0 } else { closeness / pawns.len() as f32 }
+    };
+    activity(board.king(us)) - activity(board.king(!us))
+}
+
+/// Vision weight of a target at squared Euclidean distance `dist_sq`.
+fn distance_falloff(weights: &EvalWeights, dist_sq: i32) -> f32 {
+    if weights.vision_distance_decay == 0.0 {
+        return 1.0;
+    }
+    1.0 / (1.0 + weights.vision_distance_decay * (dist_sq as f32).sqrt())
+}
+
+/// Brute-force reference for `calculate_cga_vision`: instead of blade incidence it
+/// walks the real rook/bishop rays square by square, each with its own opacity.
+/// The two must agree; a difference points at the blade tables.
+#[cfg(test)]
+pub fn reference_vision(board: &Board, weights: &EvalWeights) -> f32 {
+    let us = board.side_to_move();
+    let occupied = board.occupied();
+    let sliders = sliders(board);
+    let mut score = 0.0;
+
+    for sq in sliders {
+        let piece = board.piece_on(sq).unwrap();
+        let color = board.color_on(sq).unwrap();
+        let sign = if color == us { 1.0 } else { -1.0 };
+        let dirs: &[(i8, i8)] = match piece {
+            Piece::Rook => &ROOK_DIRS,
+            Piece::Bishop => &BISHOP_DIRS,
+            _ => &QUEEN_DIRS,
+        };
+
+        for &(df, dr) in dirs {
+            let mut opacity = 1.0;
+            let mut cur = sq;
+            let mut steps = 0;
+            while let Some(next) = cur.try_offset(df, dr) {
+                cur = next;
+                steps += 1;
+                if !occupied.has(cur) { continue; }
+                let dist = steps * steps * (df.abs() + dr.abs()) as i32;
+                let value = vision_value(board.piece_on(cur).unwrap()) * distance_falloff(weights, dist);
+                if board.color_on(cur) != Some(color) {
+                    score += sign * weights.vision_hit * value * opacity;
+                    break;
                 }
+                score += sign * weights.vision_xray * value * opacity;
+                opacity *= if extends_battery(board.piece_on(cur).unwrap(), sq, cur) {
+                    weights.battery_transparency
+                } else {
+                    weights.friendly_transparency
+                };
+                if opacity < weights.opacity_cutoff { break; }
+            }
+        }
+    }
+    score
+}
+
+/// A forked king must move, so it exposes whatever else is forked.
+fn fork_victim_value(piece: Piece) -> i32 {
+    match piece {
+        Piece::King => piece_value(Piece::Queen),
+        p => piece_value(p),
+    }
+}
+
+/// Squares a `color` knight can jump to from which it attacks two or more enemy
+/// pieces, with the forked victims. Found by chaining `KNIGHT_ADJACENCY` twice.
+pub fn knight_forks(board: &Board, color: Color) -> Vec<(Square, Vec<Square>)> {
+    let ours = board.colors(color);
+    let theirs = board.colors(!color);
+    let mut forks = Vec::new();
+
+    for knight in board.colored_pieces(color, Piece::Knight) {
+        for &dest in KNIGHT_ADJACENCY[knight as usize].iter() {
+            let dest_sq = Square::index(dest);
+            if ours.has(dest_sq) { continue; }
+            let victims: Vec<Square> = KNIGHT_ADJACENCY[dest]
+                .iter()
+                .map(|&t| Square::index(t))
+                .filter(|&t| theirs.has(t))
+                .collect();
+            if victims.len() >= 2 {
+                forks.push((dest_sq, victims));
             }
         }
-        vision_score
     }
+    forks
+}
 
-    fn material_score(&self, board: &Board, color: Color) -> i32 {
-        let mut s = 0;
-        let c = board.colors(color);
-        s += (board.pieces(Piece::Pawn) & c).len() as i32 * 100;
-        s += (board.pieces(Piece::Knight) & c).len() as i32 * 320;
-        s += (board.pieces(Piece::Bishop) & c).len() as i32 * 330;
-        s += (board.pieces(Piece::Rook) & c).len() as i32 * 500;
-        s += (board.pieces(Piece::Queen) & c).len() as i32 * 900;
-        s
+/// Line directions (one per line, not per ray) a slider's blades run along.
+fn slider_lines(piece: Piece) -> &'static [(i8, i8)] {
+    match piece {
+        Piece::Rook => &[(1, 0), (0, 1)],
+        Piece::Bishop => &[(1, 1), (1, -1)],
+        Piece::Queen => &[(1, 0), (0, 1), (1, 1), (1, -1)],
+        _ => &[],
     }
 }
 
+/// Rooks, bishops and queens of both colors: the only pieces with line blades.
+fn sliders(board: &Board) -> BitBoard {
+    board.pieces(Piece::Rook) | board.pieces(Piece::Bishop) | board.pieces(Piece::Queen)
+}
+
+/// Every line direction `slider_lines` can return, in `LINE_BLADES` slot order.
+const LINE_DIRS: [(i8, i8); 4] = [(1, 0), (0, 1), (1, 1), (1, -1)];
+
+/// Sparse line blade through each square along each of `LINE_DIRS`, built once.
+static LINE_BLADES: once_cell::sync::Lazy<[[SparseBlade; 4]; 64]> = once_cell::sync::Lazy::new(|| {
+    std::array::from_fn(|sq| {
+        std::array::from_fn(|slot| {
+            let (df, dr) = LINE_DIRS[slot];
+            let line = Multivector5D::line_blade(&BOARD_SPACE[sq], df as f32, dr as f32);
+            debug_assert!(line.is_flat(), "slider blade must be a flat through infinity");
+            SparseBlade::from_dense(&line)
+        })
+    })
+});
+
+/// Squares incident to each of `LINE_BLADES` (wedge test run once per square),
+/// so hot loops can mask the occupancy instead of repeating the test.
+static LINE_SQUARES: once_cell::sync::Lazy<[[BitBoard; 4]; 64]> = once_cell::sync::Lazy::new(|| {
+    std::array::from_fn(|sq| {
+        std::array::from_fn(|slot| {
+            let blade = &LINE_BLADES[sq][slot];
+            Square::ALL
+                .into_iter()
+                .filter(|&other| blade.is_incident(&SPARSE_BOARD_SPACE[other as usize]))
+                .fold(BitBoard::EMPTY, |bb, other| bb | other.bitboard())
+        })
+    })
+});
+
+fn line_slot(dir: (i8, i8)) -> usize {
+    LINE_DIRS.iter().position(|&d| d == dir).expect("not a slider line direction")
+}
+
+/// The line blade through `sq` along `dir`, one of `LINE_DIRS`.
+fn line_blade(sq: Square, dir: (i8, i8)) -> &'static SparseBlade {
+    &LINE_BLADES[sq as usize][line_slot(dir)]
+}
+
+/// Squares on the line through `sq` along `dir` (including `sq`).
+fn line_squares(sq: Square, dir: (i8, i8)) -> BitBoard {
+    LINE_SQUARES[sq as usize][line_slot(dir)]
+}
+
+/// Every slider (either color) whose line blade is incident to `sq`, by wedge
+/// incidence. Blades are full geometric lines, so pieces in between don't block.
+pub fn lines_through(board: &Board, sq: Square) -> Vec<(Square, Piece)> {
+    let sliders = sliders(board);
+    let point = &SPARSE_BOARD_SPACE[sq as usize];
+    let mut found = Vec::new();
+    for slider in sliders {
+        if slider == sq { continue; }
+        let piece = board.piece_on(slider).unwrap();
+        let on_line = slider_lines(piece).iter().any(|&dir| line_blade(slider, dir).is_incident(point));
+        if on_line {
+            found.push((slider, piece));
+        }
+    }
+    found
+}
+
+/// Discovered-attack candidates for `color` as (blocker to move, revealed
+/// attacker, target). Each friendly slider's line blades are met with the
+/// occupied squares (wedge incidence); along either half-line the first piece
+/// met must be a friendly blocker and the second a valuable enemy (minor or up).
+pub fn discovered_attacks(board: &Board, color: Color) -> Vec<(Square, Square, Square)> {
+    let occupied = board.occupied();
+    let sliders = sliders(board);
+    let mut found = Vec::new();
+
+    for attacker in sliders & board.colors(color) {
+        for &(df, dr) in slider_lines(board.piece_on(attacker).unwrap()) {
+            let blade = line_blade(attacker, (df, dr));
+            let mut ahead = Vec::new();
+            let mut behind = Vec::new();
+            for sq in occupied {
+                if sq == attacker || !blade.is_incident(&SPARSE_BOARD_SPACE[sq as usize]) { continue; }
+                // Signed step count along the line direction
+                let t = (sq.file() as i32 - attacker.file() as i32) * df as i32
+                    + (sq.rank() as i32 - attacker.rank() as i32) * dr as i32;
+                if t > 0 { ahead.push((t, sq)); } else { behind.push((-t, sq)); }
+            }
+
+            for mut half in [ahead, behind] {
+                if half.len() < 2 { continue; }
+                half.sort_by_key(|k| k.0);
+                let (blocker, target) = (half[0].1, half[1].1);
+                let valuable = fork_victim_value(board.piece_on(target).unwrap()) >= piece_value(Piece::Knight);
+                if board.color_on(blocker) == Some(color) && board.color_on(target) == Some(!color) && valuable {
+                    found.push((blocker, attacker, target));
+                }
+            }
+        }
+    }
+    found
+}
+
+/// Reflects a square across the d/e file boundary (a1 <-> h1).
+pub fn mirror_square(sq: usize) -> usize {
+    sq ^ 7
+}
+
+/// `get_piece_index` for every square, a1 first.
+fn site_indices(board: &Board) -> [usize; 64] {
+    std::array::from_fn(|sq| get_piece_index(board, Square::index(sq)))
+}
+
 fn get_piece_index(board: &Board, sq: Square) -> usize {
     match board.piece_on(sq) {
         None => 0,
@@ -209,3 +1586,447 @@ pub static SNAKE_PATH: [usize; 64] = [
     48, 49, 50, 51, 52, 53, 54, 55,
     63, 62, 61, 60, 59, 58, 57, 56,
 ];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::augment::{augment, Transform};
+    use crate::bench::{bench, legal_moves, random_positions};
+    use crate::rng::{RngSource, XorShift64};
+
+    #[test]
+    fn vision_matches_ray_walk() {
+        let mut evaluator = GeotensorEvaluator::new(None);
+        let mut weights = [EvalWeights::default(); 2];
+        weights[1].vision_distance_decay = 0.3;
+        for w in weights {
+            evaluator.weights = w;
+            for board in random_positions(106, 300, 60).iter().chain([Board::default()].iter()) {
+                let (cga, reference) = (evaluator.calculate_cga_vision(board), reference_vision(board, &w));
+                assert!((cga - reference).abs() < 1e-3, "{}: {} vs {}", board, cga, reference);
+            }
+        }
+    }
+
+    #[test]
+    fn occupant_table_matches_board_lookups() {
+        for board in random_positions(188, 200, 60).iter().chain([Board::default()].iter()) {
+            let table = occupants(board);
+            for sq in Square::ALL {
+                let lookup = board.piece_on(sq).zip(board.color_on(sq));
+                assert_eq!(table[sq as usize], lookup, "{} {}", board, sq);
+            }
+        }
+    }
+
+    /// Times `f` over `iters` calls; run with `cargo test -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_vision_full_board() {
+        let evaluator = GeotensorEvaluator::new(None);
+        let board = Board::default();
+        bench("vision, start position", 200_000, || evaluator.calculate_cga_vision(&board));
+        // What the table replaces: a piece_on / color_on pair per square visited
+        bench("board lookups, start position", 200_000, || {
+            board.occupied().into_iter().filter(|&sq| board.piece_on(sq).zip(board.color_on(sq)).is_some()).count() as f32
+        });
+        bench("occupant table, start position", 200_000, || {
+            let table = occupants(&board);
+            board.occupied().into_iter().filter(|&sq| table[sq as usize].is_some()).count() as f32
+        });
+    }
+
+    #[test]
+    fn vision_delta_matches_recomputation() {
+        let evaluator = GeotensorEvaluator::new(None);
+        for board in random_positions(195, 200, 40) {
+            assert_eq!(sliders(&board), board.pieces(Piece::Rook) | board.pieces(Piece::Bishop) | board.pieces(Piece::Queen));
+            let before = evaluator.calculate_cga_vision(&board);
+            for mv in legal_moves(&board) {
+                let mut after = board.clone();
+                after.play_unchecked(mv);
+                let full = -evaluator.calculate_cga_vision(&after) - before;
+                let delta = evaluator.cga_vision_delta(&board, mv);
+                assert!((full - delta).abs() < 1e-3, "{} {}: {} vs {}", board, mv, full, delta);
+            }
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn bench_vision_delta() {
+        let evaluator = GeotensorEvaluator::new(None);
+        let board = Board::default();
+        let moves = legal_moves(&board);
+        let mut i = 0;
+        bench("vision delta, start position", 200_000, || {
+            i = (i + 1) % moves.len();
+            evaluator.cga_vision_delta(&board, moves[i])
+        });
+        bench("vision recomputed, start position", 200_000, || {
+            i = (i + 1) % moves.len();
+            let mut after = board.clone();
+            after.play_unchecked(moves[i]);
+            evaluator.calculate_cga_vision(&after)
+        });
+    }
+
+    /// Random tensors whose bonds only use their first `rank` channels, so
+    /// every bond of the chain has rank at most `rank`.
+    fn low_rank_evaluator(seed: u64, rank: usize) -> GeotensorEvaluator {
+        let mut rng = XorShift64::new(seed);
+        let tensors = std::array::from_fn(|_| {
+            let mut tensor = SquareTensor::random(&mut rng);
+            for slice in tensor.data.iter_mut() {
+                for (i, row) in slice.iter_mut().enumerate() {
+                    for (m, v) in row.iter_mut().enumerate() {
+                        if i >= rank || m >= rank {
+                            *v = 0.0;
+                        }
+                    }
+                }
+            }
+            tensor.normalize();
+            tensor
+        });
+        GeotensorEvaluator::from_tensors(tensors, 0.0)
+    }
+
+    #[test]
+    fn untruncated_bonds_keep_the_contraction() {
+        const RANK: usize = 4;
+        let mut evaluator = low_rank_evaluator(103, RANK);
+        let boards = random_positions(103, 20, 40);
+        let before: Vec<_> = boards.iter().map(|b| (evaluator.contract_mps(b, &[1.0]).0, evaluator.evaluate_mps_with_entropy(b).0)).collect();
+        // Cutting every bond down to its true rank discards nothing
+        assert!(evaluator.truncate_bonds(RANK, 0.0).unwrap() < 1e-6);
+        for (board, (state, mps)) in boards.iter().zip(&before) {
+            let after = evaluator.contract_mps(board, &[1.0]).0;
+            let overlap: f32 = after.iter().zip(state).map(|(a, b)| a * b).sum();
+            assert!((overlap - 1.0).abs() < 1e-3, "{}: overlap {}", board, overlap);
+            let value = evaluator.evaluate_mps_with_entropy(board).0;
+            assert!((value - mps).abs() < 1e-2, "{}: {} vs {}", board, value, mps);
+        }
+    }
+
+    #[test]
+    fn truncation_discards_weight_and_skips_tied_tensors() {
+        let mut evaluator = GeotensorEvaluator::new_seeded(103);
+        assert!(evaluator.truncate_bonds(2, 0.0).unwrap() > 0.0);
+        let mut tied = GeotensorEvaluator::new_seeded(103).into_mirror_tied();
+        let data: Vec<_> = tied.tensors.iter().map(|t| t.data).collect();
+        assert_eq!(tied.truncate_bonds(2, 0.0), None);
+        assert!(tied.tensors.iter().map(|t| t.data).eq(data));
+    }
+
+    /// File-mirrored copies of random positions, castling rights dropped from both.
+    fn mirrored_pairs(seed: u64) -> Vec<(Board, Board)> {
+        random_positions(seed, 200, 40)
+            .iter()
+            .filter_map(|board| {
+                let mut builder = BoardBuilder::from_board(board);
+                builder.castle_rights = [CastleRights::EMPTY; 2];
+                let board = builder.build().ok()?;
+                let (mirrored, _) = augment(&board).into_iter().find(|(_, t)| *t == Transform::FileMirror)?;
+                Some((board, mirrored))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn mirror_lookup_shares_tensors_across_files() {
+        let tied = GeotensorEvaluator::new_seeded(102).into_mirror_tied();
+        assert_eq!(tied.tensors.len(), 32);
+        for sq in 0..64 {
+            assert_eq!(mirror_square(mirror_square(sq)), sq);
+            assert_eq!(mirror_square(sq) / 8, sq / 8);
+            assert_eq!(mirror_square(sq) % 8, 7 - sq % 8);
+            assert_eq!(tied.tensor(sq).data, tied.tensor(mirror_square(sq)).data, "square {}", sq);
+        }
+    }
+
+    #[test]
+    fn mirror_tied_matches_mirror_initialized_tensors() {
+        let mut tied = GeotensorEvaluator::new_seeded(102).into_mirror_tied();
+        let mut untied = GeotensorEvaluator::from_tensors(std::array::from_fn(|sq| tied.tensor(sq).clone()), tied.tension_weight);
+        untied.mirror_average = true;
+        tied.quiet_gate = false;
+        untied.quiet_gate = false;
+        for board in random_positions(102, 200, 40).iter().chain([Board::default()].iter()) {
+            assert_eq!(tied.evaluate(board), untied.evaluate(board), "{}", board);
+            assert_eq!(tied.breakdown(board), untied.breakdown(board), "{}", board);
+        }
+    }
+
+    #[test]
+    fn mirror_tied_evaluator_is_symmetric_under_file_reflection() {
+        let mut tied = GeotensorEvaluator::new_seeded(102).into_mirror_tied();
+        tied.quiet_gate = false;
+        let pairs = mirrored_pairs(102);
+        assert!(pairs.len() > 100);
+        // Only the summation order differs for the float terms
+        let close = |a: f32, b: f32| (a - b).abs() <= 1e-3 * (1.0 + a.abs());
+        for (board, mirrored) in &pairs {
+            let (a, b) = (tied.breakdown(board), tied.breakdown(mirrored));
+            assert_eq!((a.mps, a.entropy), (b.mps, b.entropy), "{} vs {}", board, mirrored);
+            assert_eq!(a.material, b.material, "{} vs {}", board, mirrored);
+            assert_eq!(a.king_activity, b.king_activity, "{} vs {}", board, mirrored);
+            assert_eq!(a.endgame_king, b.endgame_king, "{} vs {}", board, mirrored);
+            assert_eq!(a.passed_pawns, b.passed_pawns, "{} vs {}", board, mirrored);
+            assert_eq!(a.check, b.check, "{} vs {}", board, mirrored);
+            assert!(close(a.vision, b.vision), "{} vs {}: {} vs {}", board, mirrored, a.vision, b.vision);
+            assert!(close(a.knight_forks, b.knight_forks), "{} vs {}", board, mirrored);
+        }
+    }
+
+    /// The array-of-structs contraction the packed `simd` layout replaces.
+    fn contract_site_plain(tensor: &SquareTensor, state: &[f32; CHI], p_idx: usize) -> [f32; CHI] {
+        let mut next_state = [0.0; CHI];
+        for (curr_bond, &val) in state.iter().enumerate() {
+            if val == 0.0 { continue; }
+            for (next, &t) in next_state.iter_mut().zip(tensor.data[p_idx][curr_bond].iter()) {
+                *next += val * t;
+            }
+        }
+        next_state
+    }
+
+    /// Holds with and without the `simd` feature; run both to compare layouts.
+    #[test]
+    fn contraction_matches_plain_layout() {
+        let evaluator = GeotensorEvaluator::new_seeded(159);
+        let mut rng = XorShift64::new(160);
+        for sq in 0..64 {
+            for p_idx in 0..PHYSICAL_DIM {
+                let state: [f32; CHI] = std::array::from_fn(|_| rng.next_f32() - 0.5);
+                let plain = contract_site_plain(evaluator.tensor(sq), &state, p_idx);
+                assert_eq!(evaluator.contract_site(&state, sq, p_idx), plain);
+            }
+        }
+    }
+
+    #[test]
+    fn knight_next_to_the_enemy_king_is_more_active() {
+        let mut evaluator = GeotensorEvaluator::new_seeded(114);
+        evaluator.weights.piece_activity = 20.0;
+        // With white to move a knight one hop from e8 would be a check on the
+        // wrong side, so f5 (via d6 or g7) is as close as it gets; a1 is far
+        let near: Board = "4k3/p7/8/5N2/8/8/7P/4K3 w - - 0 1".parse().unwrap();
+        let far: Board = "4k3/p7/8/8/8/8/7P/N3K3 w - - 0 1".parse().unwrap();
+        let activity = |board: &Board| {
+            let mut field = GeodesicField::new();
+            field.update_costs(board);
+            evaluator.piece_activity_score(board, &field)
+        };
+        assert!(activity(&near) > activity(&far));
+
+        // Ours minus theirs: handing the move over flips the sign
+        let mut field = GeodesicField::new();
+        field.update_costs(&near);
+        let black: Board = "4k3/p7/8/5N2/8/8/7P/4K3 b - - 0 1".parse().unwrap();
+        let white_score = evaluator.piece_activity_score(&near, &field);
+        assert_eq!(evaluator.piece_activity_score(&black, &field), -white_score);
+
+        // Blended in with a caller's field only, never by plain `evaluate`
+        let base = evaluator.evaluate(&near);
+        assert_eq!(evaluator.evaluate_with_field(&near, &field), base + white_score as i32);
+        evaluator.enabled_components.remove(EvalFlags::ACTIVITY);
+        assert_eq!(evaluator.evaluate_with_field(&near, &field), base);
+    }
+
+    #[test]
+    fn disabled_components_are_not_computed() {
+        let mut evaluator = GeotensorEvaluator::new_seeded(131);
+        let board: Board = "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3".parse().unwrap();
+        let calls = || VISION_CALLS.with(|calls| calls.get());
+
+        let before = calls();
+        evaluator.breakdown(&board);
+        assert_eq!(calls(), before + 1);
+
+        evaluator.enabled_components.remove(EvalFlags::VISION);
+        let before = calls();
+        let parts = evaluator.breakdown(&board);
+        assert_eq!(calls(), before);
+        assert_eq!((parts.vision, parts.knight_forks), (0.0, 0.0));
+    }
+
+    #[test]
+    fn endgame_terms_have_their_own_flags() {
+        let mut evaluator = GeotensorEvaluator::new_seeded(131);
+        // Black in check, a passed pawn on each side, few pieces left
+        let board: Board = "R3k3/1P6/8/8/3K4/6p1/8/8 b - - 0 1".parse().unwrap();
+        let all = evaluator.breakdown(&board);
+        assert!(all.check < 0.0 && all.passed_pawns != 0.0 && all.king_activity != 0.0, "{:?}", all);
+
+        evaluator.enabled_components = EvalFlags::MATERIAL;
+        let material_only = evaluator.breakdown(&board);
+        assert_eq!(material_only.material, all.material);
+        assert_eq!(
+            (material_only.check, material_only.passed_pawns, material_only.king_activity, material_only.endgame_king),
+            (0.0, 0.0, 0.0, 0.0)
+        );
+
+        evaluator.enabled_components = EvalFlags::ALL;
+        evaluator.enabled_components.remove(EvalFlags::MATERIAL);
+        let without_material = evaluator.breakdown(&board);
+        assert_eq!(without_material.material, 0.0);
+        assert_eq!((without_material.check, without_material.passed_pawns), (all.check, all.passed_pawns));
+    }
+
+    #[test]
+    fn mobility_has_its_own_flag() {
+        let mut evaluator = GeotensorEvaluator::new_seeded(184);
+        evaluator.weights.mobility = 1.0;
+        // Only White has pieces that can move off the king
+        let board: Board = "4k3/8/8/8/8/8/8/R3K2N w - - 0 1".parse().unwrap();
+        let all = evaluator.breakdown(&board);
+        assert!(all.mobility > 0.0, "{:?}", all);
+
+        evaluator.enabled_components.remove(EvalFlags::GEODESIC);
+        assert_eq!(evaluator.breakdown(&board).mobility, all.mobility);
+        evaluator.enabled_components.remove(EvalFlags::MOBILITY);
+        assert_eq!(evaluator.breakdown(&board).mobility, 0.0);
+    }
+
+    /// Writes `values` as a little-endian tensor file, `cut` bytes short.
+    fn tensor_file(name: &str, values: &[f32], cut: usize) -> std::path::PathBuf {
+        let mut bytes: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+        bytes.truncate(bytes.len() - cut);
+        let path = std::env::temp_dir().join(format!("aethelgard_{}_{}.bin", name, std::process::id()));
+        std::fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    /// `load` of the file, which is removed afterwards.
+    fn load_file(path: std::path::PathBuf) -> Result<GeotensorEvaluator, GeoLlamaError> {
+        let loaded = GeotensorEvaluator::load(&path, 0.0);
+        std::fs::remove_file(&path).unwrap();
+        loaded
+    }
+
+    fn init_error(result: Result<GeotensorEvaluator, GeoLlamaError>) -> EvalInitError {
+        match result {
+            Err(GeoLlamaError::EvalInit(e)) => e,
+            Err(e) => panic!("not an init error: {}", e),
+            Ok(_) => panic!("bad tensors were accepted"),
+        }
+    }
+
+    #[test]
+    fn tensor_files_load_or_report_why_not() {
+        let source = GeotensorEvaluator::new_seeded(161);
+        let values: Vec<f32> = (0..64).flat_map(|sq| source.tensor(sq).data.into_iter().flatten().flatten()).collect();
+
+        let loaded = load_file(tensor_file("valid", &values, 0)).unwrap();
+        let board = Board::default();
+        assert_eq!(loaded.evaluate_mps_with_entropy(&board), source.evaluate_mps_with_entropy(&board));
+
+        assert_eq!(init_error(load_file(tensor_file("partial", &values, 2))), EvalInitError::PartialValue(values.len() * 4 - 2));
+        assert_eq!(init_error(load_file(tensor_file("ragged", &values, 4))), EvalInitError::RaggedTensorData(values.len() - 1));
+        let short = &values[..63 * SquareTensor::LEN];
+        assert_eq!(init_error(load_file(tensor_file("short", short, 0))), EvalInitError::WrongTensorCount(63));
+
+        let mut poisoned = values.clone();
+        poisoned[5 * SquareTensor::LEN + 7] = f32::NAN;
+        assert_eq!(
+            init_error(load_file(tensor_file("nan", &poisoned, 0))),
+            EvalInitError::NonFiniteValue { tensor: 5, index: 7 }
+        );
+        let missing = std::env::temp_dir().join("aethelgard_missing_tensors.bin");
+        assert_eq!(init_error(GeotensorEvaluator::load(missing, 0.0)), EvalInitError::Io(std::io::ErrorKind::NotFound));
+    }
+
+    #[test]
+    fn runtime_tensors_are_checked() {
+        let tensors: Vec<SquareTensor> = (0..64).map(|_| SquareTensor::identity()).collect();
+        assert!(GeotensorEvaluator::try_from_tensors(tensors.clone(), 0.0).is_ok());
+        assert!(GeotensorEvaluator::try_from_tensors(tensors[..32].to_vec(), 0.0).is_ok());
+        assert_eq!(init_error(GeotensorEvaluator::try_from_tensors(tensors[..10].to_vec(), 0.0)), EvalInitError::WrongTensorCount(10));
+        let mut poisoned = tensors;
+        poisoned[63].data[1][2][3] = f32::INFINITY;
+        assert_eq!(
+            init_error(GeotensorEvaluator::try_from_tensors(poisoned, 0.0)),
+            EvalInitError::NonFiniteValue { tensor: 63, index: CHI * CHI + 2 * CHI + 3 }
+        );
+    }
+
+    #[test]
+    fn seeds_reproduce_their_tensors() {
+        let tensors = |seed| {
+            let evaluator = GeotensorEvaluator::new_seeded(seed);
+            (0..64).map(|sq| evaluator.tensor(sq).data).collect::<Vec<_>>()
+        };
+        assert_eq!(tensors(117), tensors(117));
+        assert_ne!(tensors(117), tensors(118));
+        // The noise is small next to the heuristic init it perturbs
+        let plain = GeotensorEvaluator::new(None);
+        let noisy = GeotensorEvaluator::new_seeded(117);
+        for sq in 0..64 {
+            let (a, b) = (plain.tensor(sq).data, noisy.tensor(sq).data);
+            assert!(a.iter().flatten().flatten().zip(b.iter().flatten().flatten()).all(|(x, y)| (x - y).abs() <= 0.05));
+        }
+    }
+
+    #[test]
+    fn interference_tension_is_blended_by_weight() {
+        let board: Board = "7k/8/8/2ppp3/8/3K4/8/8 w - - 0 1".parse().unwrap();
+        let ours: Vec<usize> = board.colors(Color::White).into_iter().map(|sq| sq as usize).collect();
+        let mut field = GeodesicField::new();
+        field.update_costs(&board);
+        field.propagate(&ours, None, &board, f32::MAX);
+        field.propagate_retro(Square::H8 as usize, &board);
+        let tension = field.interference_tension(&ours);
+        assert!(tension > 0.0);
+
+        let mut evaluator = GeotensorEvaluator::new_seeded(122);
+        let base = evaluator.evaluate(&board);
+        assert_eq!(evaluator.evaluate_with_field(&board, &field), base);
+        evaluator.weights.interference_tension = 10.0;
+        assert_eq!(evaluator.evaluate_with_field(&board, &field), base + (10.0 * tension) as i32);
+        evaluator.enabled_components.remove(EvalFlags::GEODESIC);
+        assert_eq!(evaluator.evaluate_with_field(&board, &field), base);
+    }
+
+    #[test]
+    fn tension_weight_sets_sign_and_size_of_the_entropy_term() {
+        let mut evaluator = GeotensorEvaluator::new_seeded(153);
+        evaluator.quiet_gate = false;
+        evaluator.enabled_components = EvalFlags::ENTROPY;
+        let board: Board = "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4".parse().unwrap();
+        let entropy = |evaluator: &mut GeotensorEvaluator, weight: f32| {
+            evaluator.tension_weight = weight;
+            evaluator.breakdown(&board).entropy
+        };
+        let seeking = entropy(&mut evaluator, 1.0);
+        assert!(seeking > 0.0);
+        assert_eq!(entropy(&mut evaluator, -1.0), -seeking);
+        assert!((entropy(&mut evaluator, 3.0) - 3.0 * seeking).abs() < 1e-3);
+        assert_eq!(entropy(&mut evaluator, 0.0), 0.0);
+
+        // Credited to the engine's side: the opponent to move sees it negated
+        evaluator.tension_side = Some(Color::Black);
+        assert_eq!(entropy(&mut evaluator, 1.0), -seeking);
+        evaluator.tension_side = Some(Color::White);
+        assert_eq!(entropy(&mut evaluator, 1.0), seeking);
+    }
+
+    #[test]
+    #[ignore]
+    fn bench_contraction() {
+        let evaluator = GeotensorEvaluator::new_seeded(159);
+        let state: [f32; CHI] = std::array::from_fn(|i| 0.1 * i as f32 - 0.4);
+        let mut sq = 0;
+        let layout = if cfg!(feature = "simd") { "packed" } else { "plain" };
+        bench(&format!("contract_site, {} layout", layout), 2_000_000, || {
+            sq = (sq + 1) % 64;
+            evaluator.contract_site(&state, sq, sq % PHYSICAL_DIM)[3]
+        });
+        let boards = random_positions(161, 64, 30);
+        let mut i = 0;
+        bench(&format!("MPS contraction, {} layout", layout), 200_000, || {
+            i = (i + 1) % boards.len();
+            evaluator.evaluate_mps_with_entropy(&boards[i]).0
+        });
+    }
+}
@@ -1,6 +1,9 @@
 use std::collections::BinaryHeap;
 use std::cmp::Ordering;
 use cozy_chess::*;
+use once_cell::unsync::OnceCell;
+use crate::shadow::TacticalFeedback;
+use crate::error::GeoLlamaError;
 
 #[derive(Copy, Clone, PartialEq)]
 struct State {
@@ -12,11 +15,23 @@ impl Eq for State {}
 
 impl Ord for State {
     fn cmp(&self, other: &Self) -> Ordering {
-        // Reverse because BinaryHeap is a max-heap
-        other.cost.partial_cmp(&self.cost).unwrap_or(Ordering::Equal)
+        // Reverse because BinaryHeap is a max-heap. A total order keeps the heap
+        // consistent even if a NaN cost slipped through
+        other.cost.total_cmp(&self.cost)
     }
 }
 
+/// Default step-cost multiplier of a knight hop: it reaches two king steps
+/// away, so it shouldn't cross the board for the price of one.
+const KNIGHT_STEP_COST: f32 = 2.0;
+
+/// Default strength of the well around the enemy king: off, so costs only see
+/// occupancy and pawn chains until `set_king_gravity` turns it on.
+const DEFAULT_KING_GRAVITY: f32 = 0.0;
+
+/// Square cost added per centipawn a threat on it wins (see `from_threat_map`)
+const THREAT_COST_SCALE: f32 = 0.01;
+
 impl PartialOrd for State {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
@@ -28,6 +43,20 @@ pub struct GeodesicField {
     pub potentials: [f32; 64],
     pub retro_potentials: [f32; 64],
     pub barriers: std::collections::HashMap<usize, f32>,
+    /// Barrier mass currently contributed by `apply_danger`, so it can be retracted
+    danger: Vec<(usize, f32)>,
+    /// Position of the last `update_costs`, source of the per-piece fields
+    board: Option<Board>,
+    /// Lazily computed `field_for` results: slot 0 is `None`, then `Piece as usize + 1`
+    piece_fields: [OnceCell<[f32; 64]>; 7],
+    /// Per-piece multiplier on the square cost of each step, by `Piece as usize`
+    step_costs: [f32; 6],
+    /// Depth of the potential well `update_costs` digs around the enemy king
+    king_gravity: f32,
+    /// Arguments of the last `propagate` and `propagate_retro`, so
+    /// `from_threat_map` can recompute the potentials under the new costs
+    primal_source: Option<(Vec<usize>, Option<Piece>, f32)>,
+    retro_target: Option<usize>,
 }
 
 impl GeodesicField {
@@ -37,53 +66,158 @@ impl GeodesicField {
             potentials: [f32::MAX; 64],
             retro_potentials: [f32::MAX; 64],
             barriers: std::collections::HashMap::new(),
+            danger: Vec::new(),
+            board: None,
+            piece_fields: Default::default(),
+            step_costs: std::array::from_fn(|i| if i == Piece::Knight as usize { KNIGHT_STEP_COST } else { 1.0 }),
+            king_gravity: DEFAULT_KING_GRAVITY,
+            primal_source: None,
+            retro_target: None,
         }
     }
 
+    /// Multiplier on the square cost of one step of `piece` (1 for generic
+    /// king-step mobility).
+    pub fn step_cost(&self, piece: Option<Piece>) -> f32 {
+        piece.map_or(1.0, |p| self.step_costs[p as usize])
+    }
+
+    /// Sets the step-cost multiplier of `piece`, e.g. how much dearer a knight
+    /// hop is than a king step. Negative or non-finite costs are rejected.
+    pub fn set_step_cost(&mut self, piece: Piece, cost: f32) -> Result<(), GeoLlamaError> {
+        if !cost.is_finite() || cost < 0.0 {
+            return Err(GeoLlamaError::InvalidStepCost { piece, cost });
+        }
+        self.step_costs[piece as usize] = cost;
+        self.invalidate_piece_fields();
+        Ok(())
+    }
+
+    /// Strength of the enemy king's potential well (0 turns it off).
+    pub fn king_gravity(&self) -> f32 {
+        self.king_gravity
+    }
+
+    /// Sets how strongly square costs fall toward the enemy king: a square at
+    /// distance d has its occupancy cost divided by `1 + strength / (1 + d)`.
+    /// Costs of the last `update_costs` position are recomputed at once.
+    /// Negative or non-finite strengths are rejected.
+    pub fn set_king_gravity(&mut self, strength: f32) -> Result<(), GeoLlamaError> {
+        if !strength.is_finite() || strength < 0.0 {
+            return Err(GeoLlamaError::InvalidKingGravity(strength));
+        }
+        self.king_gravity = strength;
+        if let Some(board) = self.board.take() {
+            self.update_costs(&board);
+        }
+        Ok(())
+    }
+
+    /// Geodesic distances from the side to move's pieces of type `piece` (all of
+    /// its pieces, with generic king-step mobility, for `None`) on the position
+    /// of the last `update_costs`. Computed on first use and cached until the
+    /// costs or danger barriers change; unreachable squares stay at `f32::MAX`.
+    pub fn field_for(&self, piece: Option<Piece>) -> &[f32; 64] {
+        let slot = piece.map_or(0, |p| p as usize + 1);
+        self.piece_fields[slot].get_or_init(|| {
+            let mut dists = [f32::MAX; 64];
+            let board = match &self.board {
+                Some(board) => board,
+                None => return dists,
+            };
+            let ours = board.colors(board.side_to_move());
+            let starts = match piece {
+                Some(p) => ours & board.pieces(p),
+                None => ours,
+            };
+            let mut pq = BinaryHeap::new();
+            for sq in starts {
+                dists[sq as usize] = 0.0;
+                pq.push(State { cost: 0.0, position: sq as usize });
+            }
+            self.dijkstra_core(&mut pq, &mut dists, piece, board, f32::MAX);
+            dists
+        })
+    }
+
+    fn invalidate_piece_fields(&mut self) {
+        for cell in self.piece_fields.iter_mut() {
+            cell.take();
+        }
+    }
+
+    /// Projects ShadowGuard danger squares into the barrier map, penalizing each
+    /// square by its danger weight. Danger from the previous call is withdrawn
+    /// first; barriers added by other means are left intact.
+    pub fn apply_danger(&mut self, feedback: &TacticalFeedback) {
+        for (sq, mass) in self.danger.drain(..) {
+            if let Some(barrier) = self.barriers.get_mut(&sq) {
+                *barrier -= mass;
+                if *barrier <= 0.0 {
+                    self.barriers.remove(&sq);
+                }
+            }
+        }
+        for &(sq, mass) in &feedback.danger_squares {
+            if self.add_barrier(sq, mass).is_ok() {
+                self.danger.push((sq, mass));
+            }
+        }
+        self.invalidate_piece_fields();
+    }
+
     /// Primal Wave: Propagation from origin squares (forward in time)
-    pub fn propagate(&mut self, start_sqs: &[usize], piece_type: Option<Piece>, board: &Board) {
-        self.potentials.fill(f32::MAX);
+    /// Squares costing more than `max_cost` to reach are left at `f32::MAX`
+    /// (pass `f32::MAX` to flood the whole board); the others get exact potentials.
+    pub fn propagate(&mut self, start_sqs: &[usize], piece_type: Option<Piece>, board: &Board, max_cost: f32) {
+        let mut potentials = [f32::MAX; 64];
         let mut pq = BinaryHeap::new();
 
         for &sq in start_sqs {
-            self.potentials[sq] = 0.0;
+            potentials[sq] = 0.0;
             pq.push(State { cost: 0.0, position: sq });
         }
 
-        Self::dijkstra_core(&mut pq, &mut self.potentials, piece_type, board, &self.costs, &self.barriers);
+        self.dijkstra_core(&mut pq, &mut potentials, piece_type, board, max_cost);
+        self.potentials = potentials;
+        self.primal_source = Some((start_sqs.to_vec(), piece_type, max_cost));
     }
 
     /// Retrocausal Wave: Propagation backward from the goal (e.g., enemy king)
     pub fn propagate_retro(&mut self, target_sq: usize, board: &Board) {
-        self.retro_potentials.fill(f32::MAX);
+        let mut retro_potentials = [f32::MAX; 64];
         let mut pq = BinaryHeap::new();
 
-        self.retro_potentials[target_sq] = 0.0;
+        retro_potentials[target_sq] = 0.0;
         pq.push(State { cost: 0.0, position: target_sq });
 
         // Goal propagation uses generic piece mobility or "King" as it's the target point
-        Self::dijkstra_core(&mut pq, &mut self.retro_potentials, None, board, &self.costs, &self.barriers);
+        self.dijkstra_core(&mut pq, &mut retro_potentials, None, board, f32::MAX);
+        self.retro_potentials = retro_potentials;
+        self.retro_target = Some(target_sq);
     }
 
     fn dijkstra_core(
+        &self,
         pq: &mut BinaryHeap<State>, 
         dists: &mut [f32; 64], 
         piece_type: Option<Piece>, 
         board: &Board,
-        costs: &[f32; 64],
-        barriers: &std::collections::HashMap<usize, f32>,
+        max_cost: f32,
     ) {
+        let step_cost = self.step_cost(piece_type);
         while let Some(State { cost, position }) = pq.pop() {
             if cost > dists[position] {
                 continue;
             }
 
             for neighbor in get_dynamic_neighbors_static(position, piece_type, board) {
-                let base_cost = costs[neighbor];
-                let barrier_cost = barriers.get(&neighbor).cloned().unwrap_or(0.0);
+                let base_cost = self.costs[neighbor] * step_cost;
+                let barrier_cost = self.barriers.get(&neighbor).cloned().unwrap_or(0.0);
                 let next_cost = cost + base_cost + barrier_cost;
-                
-                if next_cost < dists[neighbor] {
+                // Past the cutoff nothing is recorded, so it is never popped either.
+                // Both comparisons are false for NaN, so a NaN cost never lands either
+                if next_cost <= max_cost && next_cost < dists[neighbor] {
                     dists[neighbor] = next_cost;
                     pq.push(State { cost: next_cost, position: neighbor });
                 }
@@ -91,22 +225,81 @@ impl GeodesicField {
         }
     }
 
+    /// Geodesic distances from `origin` for a given piece type, without touching
+    /// the stored potentials.
+    pub fn distances_from(&self, origin: usize, piece_type: Option<Piece>, board: &Board) -> [f32; 64] {
+        let mut dists = [f32::MAX; 64];
+        let mut pq = BinaryHeap::new();
+
+        dists[origin] = 0.0;
+        pq.push(State { cost: 0.0, position: origin });
+
+        self.dijkstra_core(&mut pq, &mut dists, piece_type, board, f32::MAX);
+        dists
+    }
+
     /// Finds the best move target where Primal and Retro waves meet constructively
     pub fn solve_flow(&self, start_sqs: &[usize]) -> Option<usize> {
-        let mut best_sq = None;
+        self.solve_flow_weighted(start_sqs, 1.0, 1.0).map(|(sq, _)| sq)
+    }
+
+    /// `solve_flow` with a weighted action S = primal_w * Primal + retro_w * Retro.
+    /// Raising `retro_w` favors squares nearer the goal over cheap-to-reach ones.
+    /// Returns the chosen square and its action.
+    pub fn solve_flow_weighted(&self, start_sqs: &[usize], primal_w: f32, retro_w: f32) -> Option<(usize, f32)> {
+        let mut best = None;
         let mut min_action = f32::MAX;
 
         for &sq in start_sqs {
             for neighbor in get_generic_neighbors(sq) {
-                // Constructive Interference: S = Primal + Retro
-                let action = self.potentials[neighbor] + self.retro_potentials[neighbor];
+                // Constructive Interference
+                let action = primal_w * self.potentials[neighbor] + retro_w * self.retro_potentials[neighbor];
                 if action < min_action {
                     min_action = action;
-                    best_sq = Some(neighbor);
+                    best = Some((neighbor, action));
                 }
             }
         }
-        best_sq
+        best
+    }
+
+    /// How sharply the summed action (Primal + Retro) varies around the square
+    /// where the waves meet: the mean absolute action difference to its finite
+    /// neighbors. Converging attack/defense waves give steep walls (high tension);
+    /// placid positions give a flat basin.
+    pub fn interference_tension(&self, start_sqs: &[usize]) -> f32 {
+        let meet = match self.solve_flow(start_sqs) {
+            Some(sq) => sq,
+            None => return 0.0,
+        };
+        let action = |sq: usize| self.potentials[sq] + self.retro_potentials[sq];
+        let center = action(meet);
+        if !center.is_finite() || center >= f32::MAX {
+            return 0.0;
+        }
+
+        let mut total = 0.0;
+        let mut count = 0;
+        for neighbor in get_generic_neighbors(meet) {
+            let a = action(neighbor);
+            if a.is_finite() && a < f32::MAX {
+                total += (a - center).abs();
+                count += 1;
+            }
+        }
+        if count == 0 { 0.0 } else { total / count as f32 }
+    }
+
+    /// Adds `mass` to the barrier on `sq`. Dijkstra needs finite, non-negative
+    /// step costs, so NaN, infinite or negative masses are rejected, as are
+    /// squares off the board.
+    pub fn add_barrier(&mut self, sq: usize, mass: f32) -> Result<(), GeoLlamaError> {
+        if sq >= 64 || !mass.is_finite() || mass < 0.0 {
+            return Err(GeoLlamaError::InvalidBarrier { square: sq, mass });
+        }
+        *self.barriers.entry(sq).or_insert(0.0) += mass;
+        self.invalidate_piece_fields();
+        Ok(())
     }
 
     pub fn get_dynamic_neighbors(&self, sq: usize, piece_type: Option<Piece>, board: &Board) -> Vec<usize> {
@@ -118,6 +311,7 @@ impl GeodesicField {
         let us = board.side_to_move();
         let pawns = board.pieces(Piece::Pawn);
         let their_pawns = pawns & board.colors(!us);
+        let their_king = board.colored_pieces(!us, Piece::King);
 
         for sq in 0..64 {
             let square = Square::index(sq);
@@ -131,6 +325,13 @@ impl GeodesicField {
                 }
             }
 
+            // Gravity well: squares near the enemy king are cheaper to cross.
+            // It scales the occupancy cost only, so pawn-chain barriers keep their height
+            if self.king_gravity > 0.0 {
+                let dist_to_king = self.min_dist_to_bitboard(square, their_king);
+                base_cost /= 1.0 + self.king_gravity / (1.0 + dist_to_king);
+            }
+
             // Logarithmic Barrier for Pawn Chains
             // Crossing an opponent's pawn chain is topologically expensive
             let dist_to_chain = self.min_dist_to_bitboard(square, their_pawns);
@@ -141,6 +342,37 @@ impl GeodesicField {
 
             self.costs[sq] = base_cost;
         }
+        self.board = Some(board.clone());
+        self.invalidate_piece_fields();
+    }
+
+    /// Rebuilds the costs for `board` (`update_costs`) and raises each square
+    /// of `threats`, e.g. `ShadowGuard::threat_map`, by its weight in centipawns
+    /// times `THREAT_COST_SCALE`, so planned routes avoid what the opponent
+    /// threatens. Unlike barriers these live in `costs`, so the next
+    /// `update_costs` clears them. The stored potentials are recomputed from
+    /// the sources of the last `propagate` and `propagate_retro` (those never
+    /// propagated stay as they are); per-piece fields follow on demand.
+    /// Squares off the board and negative or non-finite weights are rejected
+    /// before anything changes.
+    // Updates in place: "from" names the source of the costs, not a constructor
+    #[allow(clippy::wrong_self_convention)]
+    pub fn from_threat_map(&mut self, board: &Board, threats: &[(usize, f32)]) -> Result<(), GeoLlamaError> {
+        if let Some(&(square, mass)) = threats.iter().find(|&&(sq, w)| sq >= 64 || !w.is_finite() || w < 0.0) {
+            return Err(GeoLlamaError::InvalidBarrier { square, mass });
+        }
+        self.update_costs(board);
+        for &(sq, weight) in threats {
+            self.costs[sq] += weight * THREAT_COST_SCALE;
+        }
+        self.invalidate_piece_fields();
+        if let Some((start_sqs, piece_type, max_cost)) = self.primal_source.take() {
+            self.propagate(&start_sqs, piece_type, board, max_cost);
+        }
+        if let Some(target_sq) = self.retro_target {
+            self.propagate_retro(target_sq, board);
+        }
+        Ok(())
     }
 
     fn min_dist_to_bitboard(&self, sq: Square, bb: BitBoard) -> f32 {
@@ -157,13 +389,92 @@ impl GeodesicField {
     }
 }
 
+/// Action slack (one plain step) within which a destination still counts as
+/// part of the low-action basin in `geometric_moves`.
+const GEOMETRIC_ACTION_MARGIN: f32 = 1.0;
+
+/// Legal moves landing where the primal and retro waves meet constructively:
+/// destinations whose action (Primal + Retro) is within one step of the least
+/// action any legal move reaches. Needs both waves propagated; a cheap
+/// ordering hint (see `ShadowGuard::root_hints`), not a pruning rule.
+pub fn geometric_moves(board: &Board, field: &GeodesicField) -> Vec<Move> {
+    let action = |sq: Square| field.potentials[sq as usize] + field.retro_potentials[sq as usize];
+    let mut moves = Vec::new();
+    board.generate_moves(|mvs| {
+        moves.extend(mvs.into_iter().filter(|mv| action(mv.to) < f32::MAX));
+        false
+    });
+    let least = moves.iter().map(|mv| action(mv.to)).fold(f32::INFINITY, f32::min);
+    moves.retain(|mv| action(mv.to) <= least + GEOMETRIC_ACTION_MARGIN);
+    moves
+}
+
+/// A multi-move plan ("route the knight to d5") that persists between searches.
+pub struct StrategicPlan {
+    pub piece: Piece,
+    /// Square the planned piece currently stands on.
+    pub square: usize,
+    pub target: usize,
+    active: bool,
+}
+
+impl StrategicPlan {
+    pub fn new(piece: Piece, square: usize, target: usize) -> Self {
+        Self { piece, square, target, active: true }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Picks the legal move of the planned piece that most reduces its geodesic
+    /// distance to the target. The plan is invalidated (returning None) once the
+    /// target is reached, the piece is gone, or no move makes progress.
+    pub fn advance(&mut self, board: &Board, field: &GeodesicField) -> Option<Move> {
+        if !self.active {
+            return None;
+        }
+        let from = Square::index(self.square);
+        let ours = board.colors(board.side_to_move());
+        if self.square == self.target || board.piece_on(from) != Some(self.piece) || !ours.has(from) {
+            self.active = false;
+            return None;
+        }
+
+        // Distances measured from the target with the piece's own mobility
+        let dists = field.distances_from(self.target, Some(self.piece), board);
+        let mut best: Option<(f32, Move)> = None;
+        board.generate_moves_for(from.bitboard(), |mvs| {
+            for mv in mvs {
+                let d = dists[mv.to as usize];
+                if best.is_none_or(|(bd, _)| d < bd) {
+                    best = Some((d, mv));
+                }
+            }
+            false
+        });
+
+        match best {
+            Some((d, mv)) if d < dists[self.square] => {
+                self.square = mv.to as usize;
+                Some(mv)
+            }
+            _ => {
+                self.active = false;
+                None
+            }
+        }
+    }
+}
+
 pub fn get_dynamic_neighbors_static(sq: usize, piece_type: Option<Piece>, board: &Board) -> Vec<usize> {
     let mut neighbors = get_generic_neighbors(sq);
     
     match piece_type {
         Some(Piece::Knight) => {
             // Topological Sewing: Knights fold the manifold
-            // The Knight "wormholes" to its destination in 1 step
+            // The Knight "wormholes" to its destination in 1 step, priced by
+            // its step cost (see `GeodesicField::set_step_cost`)
             neighbors.clear(); 
             neighbors.extend_from_slice(&KNIGHT_ADJACENCY[sq]);
         }
@@ -179,10 +490,56 @@ pub fn get_dynamic_neighbors_static(sq: usize, piece_type: Option<Piece>, board:
                 neighbors.push(target as usize);
             }
         }
+        Some(Piece::King) => {
+            let square = Square::index(sq);
+            if let Some(color) = board.color_on(square) {
+                if board.king(color) == square {
+                    for dest in castle_destinations(board, color) {
+                        if !neighbors.contains(&dest) {
+                            neighbors.push(dest);
+                        }
+                    }
+                }
+            }
+        }
         _ => {}
     }
     neighbors
 }
+
+/// Squares the king of `color` lands on by castling with its remaining rights.
+/// Rook files come from the board's castle rights, so Chess960 starts work; only
+/// the path must be clear (attacked squares are left to move generation).
+pub fn castle_destinations(board: &Board, color: Color) -> Vec<usize> {
+    let king = board.king(color);
+    let back_rank = Rank::First.relative_to(color);
+    let rights = board.castle_rights(color);
+    let mut dests = Vec::new();
+
+    for (rook_file, king_file, rook_dest_file) in [
+        (rights.short, File::G, File::F),
+        (rights.long, File::C, File::D),
+    ] {
+        let rook_file = match rook_file {
+            Some(file) => file,
+            None => continue,
+        };
+        let rook = Square::new(rook_file, back_rank);
+        let king_dest = Square::new(king_file, back_rank);
+        let rook_dest = Square::new(rook_dest_file, back_rank);
+        if king_dest == king {
+            continue;
+        }
+        let blockers = board.occupied() ^ king.bitboard() ^ rook.bitboard();
+        let path = get_between_rays(king, king_dest) | king_dest.bitboard()
+            | get_between_rays(rook, rook_dest) | rook_dest.bitboard();
+        if (path & blockers).is_empty() {
+            dests.push(king_dest as usize);
+        }
+    }
+    dests
+}
+
 pub fn get_generic_neighbors(sq: usize) -> Vec<usize> {
     let mut neighbors = Vec::new();
     let row = sq / 8;
@@ -202,8 +559,7 @@ pub fn get_generic_neighbors(sq: usize) -> Vec<usize> {
 }
 
 pub static KNIGHT_ADJACENCY: once_cell::sync::Lazy<[Vec<usize>; 64]> = once_cell::sync::Lazy::new(|| {
-    let mut table = Vec::with_capacity(64);
-    for sq in 0..64 {
+    std::array::from_fn(|sq| {
         let mut moves = Vec::new();
         let r = (sq / 8) as i32;
         let c = (sq % 8) as i32;
@@ -215,7 +571,225 @@ pub static KNIGHT_ADJACENCY: once_cell::sync::Lazy<[Vec<usize>; 64]> = once_cell
                 moves.push((nr * 8 + nc) as usize);
             }
         }
-        table.push(moves);
-    }
-    table.try_into().unwrap()
+        moves
+    })
 });
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Squares of the stored geodesic from the `propagate` start to `target`,
+    /// start excluded. A step's cost depends only on the square entered, so
+    /// the predecessor of a square is its neighbor with the lowest potential.
+    fn geodesic_path(field: &GeodesicField, board: &Board, target: usize) -> Vec<usize> {
+        let mut path = vec![target];
+        let mut sq = target;
+        while field.potentials[sq] > 0.0 {
+            sq = field
+                .get_dynamic_neighbors(sq, None, board)
+                .into_iter()
+                .min_by(|&a, &b| field.potentials[a].total_cmp(&field.potentials[b]))
+                .unwrap();
+            path.push(sq);
+        }
+        path.pop();
+        path.reverse();
+        path
+    }
+
+    #[test]
+    fn threatened_paths_reroute() {
+        let board: Board = "7k/8/8/8/8/8/8/K7 w - - 0 1".parse().unwrap();
+        let mut field = GeodesicField::new();
+        field.update_costs(&board);
+        field.propagate(&[Square::A1 as usize], None, &board, f32::MAX);
+
+        let (threatened_target, safe_target) = (Square::H1 as usize, Square::A8 as usize);
+        let threatened_path = geodesic_path(&field, &board, threatened_target);
+        let safe_path = geodesic_path(&field, &board, safe_target);
+        let threatened = threatened_path[threatened_path.len() / 2];
+        assert!(!safe_path.contains(&threatened));
+
+        field.from_threat_map(&board, &[(threatened, 5000.0)]).unwrap();
+        assert!(!geodesic_path(&field, &board, threatened_target).contains(&threatened));
+        assert_eq!(geodesic_path(&field, &board, safe_target), safe_path);
+    }
+
+    #[test]
+    fn invalid_threats_change_nothing() {
+        let board = Board::default();
+        let mut field = GeodesicField::new();
+        field.update_costs(&board);
+        let costs = field.costs;
+        assert!(field.from_threat_map(&board, &[(10, 100.0), (64, 1.0)]).is_err());
+        assert!(field.from_threat_map(&board, &[(10, -1.0)]).is_err());
+        assert_eq!(field.costs, costs);
+    }
+
+    #[test]
+    fn danger_squares_are_detoured_and_withdrawn() {
+        let board: Board = "7k/8/8/8/8/8/8/K7 w - - 0 1".parse().unwrap();
+        let mut field = GeodesicField::new();
+        field.update_costs(&board);
+        field.propagate(&[Square::A1 as usize], None, &board, f32::MAX);
+        let target = Square::H1 as usize;
+        let path = geodesic_path(&field, &board, target);
+        let danger = path[path.len() / 2];
+
+        let feedback = TacticalFeedback { is_safe: false, danger_squares: vec![(danger, 1000.0)], score: -300 };
+        field.apply_danger(&feedback);
+        field.propagate(&[Square::A1 as usize], None, &board, f32::MAX);
+        let detour = geodesic_path(&field, &board, target);
+        assert!(!detour.contains(&danger));
+        assert_eq!(detour.last(), Some(&target));
+
+        // The next verdict replaces this one
+        field.apply_danger(&TacticalFeedback { is_safe: true, danger_squares: Vec::new(), score: 0 });
+        assert!(field.barriers.is_empty());
+        field.propagate(&[Square::A1 as usize], None, &board, f32::MAX);
+        assert_eq!(geodesic_path(&field, &board, target), path);
+    }
+
+    #[test]
+    fn retro_weight_pulls_the_target_toward_the_goal() {
+        let board: Board = "7k/8/8/8/8/8/8/K7 w - - 0 1".parse().unwrap();
+        let mut field = GeodesicField::new();
+        field.update_costs(&board);
+        let (home, forward) = (Square::A1 as usize, Square::E4 as usize);
+        field.propagate(&[home], None, &board, f32::MAX);
+        field.propagate_retro(Square::H8 as usize, &board);
+
+        // Reach alone: next to the wave's source
+        let (near, _) = field.solve_flow_weighted(&[home, forward], 1.0, 0.0).unwrap();
+        assert!(get_generic_neighbors(home).contains(&near));
+        // Weighted toward the goal: past the forward square, nearest the king
+        let (far, action) = field.solve_flow_weighted(&[home, forward], 1.0, 10.0).unwrap();
+        assert_eq!(far, Square::E5 as usize);
+        assert_eq!(action, field.potentials[far] + 10.0 * field.retro_potentials[far]);
+        assert_eq!(field.solve_flow(&[home, forward]), field.solve_flow_weighted(&[home, forward], 1.0, 1.0).map(|(sq, _)| sq));
+    }
+
+    /// Field of the side to move's pieces against the enemy king, both waves propagated.
+    fn attack_field(board: &Board) -> (GeodesicField, Vec<usize>) {
+        let mut field = GeodesicField::new();
+        field.update_costs(board);
+        let ours: Vec<usize> = board.colors(board.side_to_move()).into_iter().map(|sq| sq as usize).collect();
+        field.propagate(&ours, None, board, f32::MAX);
+        field.propagate_retro(board.king(!board.side_to_move()) as usize, board);
+        (field, ours)
+    }
+
+    #[test]
+    fn pawn_walls_raise_interference_tension() {
+        let tension = |fen: &str| {
+            let (field, ours) = attack_field(&fen.parse().unwrap());
+            field.interference_tension(&ours)
+        };
+        // The pawn chain's barrier steepens the action around the meeting square
+        let placid = tension("7k/8/8/8/8/8/8/K7 w - - 0 1");
+        let walled = tension("7k/8/8/2ppp3/8/3K4/8/8 w - - 0 1");
+        assert!(walled > placid, "{} vs {}", walled, placid);
+        // Nothing propagated, nothing to measure
+        assert_eq!(GeodesicField::new().interference_tension(&[Square::A1 as usize]), 0.0);
+    }
+
+    #[test]
+    fn chess960_castling_with_a_neighboring_rook() {
+        let dests = |fen: &str| {
+            let mut dests = castle_destinations(&Board::from_fen(fen, true).unwrap(), Color::White);
+            dests.sort();
+            dests
+        };
+        // The g1 rook sits on the king's short destination and the king on its own
+        assert_eq!(dests("r4kr1/pppppppp/8/8/8/8/PPPPPPPP/R4KR1 w GAga - 0 1"), [Square::C1 as usize, Square::G1 as usize]);
+        // A knight in the long path blocks only that side
+        assert_eq!(dests("r4kr1/pppppppp/8/8/8/8/PPPPPPPP/RN3KR1 w GAga - 0 1"), [Square::G1 as usize]);
+        // Already on g1 there is nowhere to go short
+        assert_eq!(dests("r5kr/pppppppp/8/8/8/8/PPPPPPPP/R5KR w HAha - 0 1"), [Square::C1 as usize]);
+    }
+
+    #[test]
+    fn piece_fields_are_cached_until_the_board_changes() {
+        let board: Board = "4k3/8/8/8/8/8/8/1N2K3 w - - 0 1".parse().unwrap();
+        let mut field = GeodesicField::new();
+        assert!(field.field_for(Some(Piece::Knight)).iter().all(|&d| d == f32::MAX));
+        field.update_costs(&board);
+        let first = *field.field_for(Some(Piece::Knight));
+        assert_eq!(first, field.distances_from(Square::B1 as usize, Some(Piece::Knight), &board));
+        assert!(std::ptr::eq(field.field_for(Some(Piece::Knight)), field.field_for(Some(Piece::Knight))));
+
+        let moved: Board = "4k3/8/8/8/8/2N5/8/4K3 w - - 0 1".parse().unwrap();
+        field.update_costs(&moved);
+        let second = *field.field_for(Some(Piece::Knight));
+        assert_ne!(second, first);
+        assert_eq!(second[Square::C3 as usize], 0.0);
+
+        field.add_barrier(Square::D5 as usize, 50.0).unwrap();
+        assert!(field.field_for(Some(Piece::Knight))[Square::D5 as usize] > second[Square::D5 as usize]);
+    }
+
+    #[test]
+    fn geometric_moves_are_legal_moves() {
+        for board in crate::bench::random_positions(160, 50, 40) {
+            if !board.generate_moves(|_| true) {
+                continue;
+            }
+            let (field, _) = attack_field(&board);
+            let moves = geometric_moves(&board, &field);
+            assert!(!moves.is_empty(), "{}", board);
+            assert!(moves.iter().all(|&mv| board.is_legal(mv)), "{}", board);
+        }
+        // Without waves there is no basin to land in
+        assert!(geometric_moves(&Board::default(), &GeodesicField::new()).is_empty());
+    }
+
+    #[test]
+    fn propagation_stops_at_max_cost() {
+        let board: Board = "7k/8/8/2ppp3/8/3K4/8/8 w - - 0 1".parse().unwrap();
+        let mut field = GeodesicField::new();
+        field.update_costs(&board);
+        let start = [Square::D3 as usize];
+        field.propagate(&start, None, &board, f32::MAX);
+        let full = field.potentials;
+        field.propagate(&start, None, &board, 3.0);
+        for sq in 0..64 {
+            if full[sq] <= 3.0 {
+                assert_eq!(field.potentials[sq], full[sq]);
+            } else {
+                assert_eq!(field.potentials[sq], f32::MAX);
+            }
+        }
+        assert!(full.iter().any(|&d| d > 3.0 && d < f32::MAX));
+    }
+
+    #[test]
+    fn invalid_barriers_are_rejected() {
+        let mut field = GeodesicField::new();
+        for (sq, mass) in [(10, f32::NAN), (10, f32::INFINITY), (10, -1.0), (64, 1.0)] {
+            assert!(matches!(field.add_barrier(sq, mass), Err(GeoLlamaError::InvalidBarrier { square, .. }) if square == sq));
+        }
+        assert!(field.barriers.is_empty());
+        field.add_barrier(10, 2.0).unwrap();
+        field.add_barrier(10, 3.0).unwrap();
+        assert_eq!(field.barriers[&10], 5.0);
+    }
+
+    #[test]
+    fn knight_hops_cost_two_king_steps() {
+        let board: Board = "7k/8/8/8/8/8/8/K7 w - - 0 1".parse().unwrap();
+        let mut field = GeodesicField::new();
+        let (from, hop, corner) = (Square::B1 as usize, Square::C3 as usize, Square::H8 as usize);
+        let knight = field.distances_from(from, Some(Piece::Knight), &board);
+        let king = field.distances_from(from, Some(Piece::King), &board);
+        assert_eq!(knight[hop], KNIGHT_STEP_COST);
+        assert_eq!(king[hop], 2.0);
+        // Across the board the knight is no shortcut
+        assert!(knight[corner] > king[corner]);
+
+        field.set_step_cost(Piece::Knight, 1.0).unwrap();
+        assert_eq!(field.distances_from(from, Some(Piece::Knight), &board)[hop], 1.0);
+        assert!(field.set_step_cost(Piece::Knight, f32::NAN).is_err());
+        assert_eq!(field.step_cost(Some(Piece::Knight)), 1.0);
+    }
+}
@@ -1,30 +1,118 @@
-use crate::cga::{Multivector5D, CAYLEY_TABLE};
+use once_cell::sync::OnceCell;
+use crate::cga::{cayley_table, MAX_DIM};
 
 /// Precomputed tables for Result-Centric Geometric Product.
 /// For each output component k (0..32), we list the 32 pairs of (a_idx, b_idx, sign) 
 /// that contribute to it.
 /// Structure: [Output_Lane][Input_Pair_Index] -> (Sign*Bucket, A_Index, B_Index)
 /// We flatten this for cache locality.
-pub static GP_MAP: once_cell::sync::Lazy<Vec<(f32, usize, usize)>> = once_cell::sync::Lazy::new(|| {
-    // There are 32 output components.
-    // For each output 'k', there are 32 pairs of (a, b) such that a * b = +/- k.
-    // Total entries = 32 * 32 = 1024.
+pub static GP_MAP: once_cell::sync::Lazy<Vec<(f32, usize, usize)>> = once_cell::sync::Lazy::new(|| build_gp_map(5));
+
+/// GP map of Cl(dim-1, 1) laid out like `GP_MAP`, which it returns for Cl(4,1);
+/// the other algebras' maps are built on first use.
+pub fn gp_map(dim: usize) -> &'static [(f32, usize, usize)] {
+    static MAPS: [OnceCell<Vec<(f32, usize, usize)>>; MAX_DIM + 1] = [const { OnceCell::new() }; MAX_DIM + 1];
+    if dim == 5 {
+        return &GP_MAP;
+    }
+    assert!(dim <= MAX_DIM, "no GP map for a {}-dimensional algebra", dim);
+    MAPS[dim].get_or_init(|| build_gp_map(dim))
+}
+
+fn build_gp_map(dim: usize) -> Vec<(f32, usize, usize)> {
+    // There are N output components.
+    // For each output 'k', there are N pairs of (a, b) such that a * b = +/- k.
+    // Total entries = N * N (1024 for Cl(4,1)).
     // We store them ordered by 'k' to allow linear writing of the result.
+    let n = 1 << dim;
+    let cayley = cayley_table(dim);
+    let mut map = Vec::with_capacity(n * n);
     
-    let mut map = Vec::with_capacity(1024);
-    
-    for k in 0..32 {
-        for a in 0..32 {
+    for k in 0..n {
+        for a in 0..n {
             // We need to find 'b' such that basis(a) * basis(b) = +/- basis(k).
             // In a group, b = a^-1 * k. 
-            // We brute force the CAYLEY_TABLE to find the matching pair.
-            for b in 0..32 {
-                let (sign, res_k) = CAYLEY_TABLE[a][b];
+            // We brute force the Cayley table to find the matching pair.
+            for b in 0..n {
+                let (sign, res_k) = cayley[a * n + b];
                 if res_k == k {
                    map.push((sign, a, b));
                 }
             }
         }
+        // geometric_product reads exactly N entries per lane; a short or long
+        // bucket would shift every later lane, so refuse to build a corrupt map.
+        let contributors = map.len() - k * n;
+        assert_eq!(
+            contributors, n,
+            "GP map: output lane {} has {} contributing pairs, expected {} (Cayley table is not a group table)",
+            k, contributors, n
+        );
     }
     map
-});
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bench::{bench, random_multivector};
+    use crate::cga::Multivector5D;
+    use crate::rng::XorShift64;
+
+    /// Product scattered straight from the Cayley table, without the GP map.
+    fn cayley_product(a: &Multivector5D, b: &Multivector5D) -> Multivector5D {
+        let table = cayley_table(Multivector5D::DIM);
+        let mut res = Multivector5D::zero();
+        for i in 0..32 {
+            for j in 0..32 {
+                let (sign, k) = table[i * 32 + j];
+                res.lanes[k] += sign * a.lanes[i] * b.lanes[j];
+            }
+        }
+        res
+    }
+
+    #[test]
+    fn buckets_hold_n_pairs_in_left_lane_order() {
+        for dim in 1..=MAX_DIM {
+            let n = 1 << dim;
+            let map = gp_map(dim);
+            assert_eq!(map.len(), n * n);
+            for (k, bucket) in map.chunks_exact(n).enumerate() {
+                // The `a` loop of build_gp_map emits one pair per left lane in
+                // ascending order, so `self.lanes` is read front to back
+                assert!(bucket.iter().map(|&(_, a, _)| a).eq(0..n), "dim {} lane {}", dim, k);
+                assert!(bucket.iter().all(|&(_, a, b)| cayley_table(dim)[a * n + b].1 == k));
+            }
+        }
+    }
+
+    #[test]
+    fn product_matches_cayley_scatter() {
+        let mut rng = XorShift64::new(181);
+        for _ in 0..50 {
+            let (a, b) = (random_multivector(&mut rng), random_multivector(&mut rng));
+            let (gathered, scattered) = (a.geometric_product(&b), cayley_product(&a, &b));
+            for k in 0..32 {
+                assert!((gathered.lanes[k] - scattered.lanes[k]).abs() < 1e-5, "lane {}", k);
+            }
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn bench_gp_map() {
+        let mut rng = XorShift64::new(182);
+        let operands: Vec<Multivector5D> = (0..64).map(|_| random_multivector(&mut rng)).collect();
+        for (label, product) in [
+            ("Cayley scatter", cayley_product as fn(&Multivector5D, &Multivector5D) -> Multivector5D),
+            ("GP map gather", |a: &Multivector5D, b: &Multivector5D| a.geometric_product(b)),
+        ] {
+            let mut i = 0;
+            bench(label, 100_000, || {
+                i += 1;
+                std::hint::black_box(product(&operands[i % 64], &operands[(i + 1) % 64])).lanes[5]
+            });
+        }
+    }
+}
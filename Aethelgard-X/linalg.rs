@@ -0,0 +1,156 @@
+//! Minimal dense linear algebra for the tensor network (no external deps).
+//! Matrices are small (bond dimension 10), so everything runs in f64 on the stack.
+
+pub const N: usize = 10;
+
+pub type Mat = [[f64; N]; N];
+
+/// Thin QR by modified Gram-Schmidt: `a` (rows x N) is overwritten with Q,
+/// and R (N x N, upper triangular) is returned so that A = Q * R.
+/// Rank-deficient columns come back as zero columns of Q with R[j][j] = 0.
+pub fn qr_in_place(a: &mut [[f64; N]]) -> Mat {
+    let mut r = [[0.0; N]; N];
+    for j in 0..N {
+        for i in 0..j {
+            let dot: f64 = a.iter().map(|row| row[i] * row[j]).sum();
+            r[i][j] = dot;
+            for row in a.iter_mut() {
+                row[j] -= dot * row[i];
+            }
+        }
+        let norm = a.iter().map(|row| row[j] * row[j]).sum::<f64>().sqrt();
+        if norm > 1e-12 {
+            r[j][j] = norm;
+            for row in a.iter_mut() {
+                row[j] /= norm;
+            }
+        } else {
+            for row in a.iter_mut() {
+                row[j] = 0.0;
+            }
+        }
+    }
+    r
+}
+
+/// One-sided Jacobi SVD: C = U * diag(S) * V^T, singular values sorted descending.
+pub fn svd(c: &Mat) -> (Mat, [f64; N], Mat) {
+    let mut u = *c;
+    let mut v = [[0.0; N]; N];
+    for (i, row) in v.iter_mut().enumerate() {
+        row[i] = 1.0;
+    }
+
+    for _sweep in 0..60 {
+        let mut rotated = false;
+        for p in 0..N {
+            for q in (p + 1)..N {
+                let mut alpha = 0.0;
+                let mut beta = 0.0;
+                let mut gamma = 0.0;
+                for row in u.iter() {
+                    alpha += row[p] * row[p];
+                    beta += row[q] * row[q];
+                    gamma += row[p] * row[q];
+                }
+                if gamma.abs() <= 1e-15 * (alpha * beta).sqrt() || gamma == 0.0 {
+                    continue;
+                }
+                rotated = true;
+                let zeta = (beta - alpha) / (2.0 * gamma);
+                let t = zeta.signum() / (zeta.abs() + (1.0 + zeta * zeta).sqrt());
+                let cs = 1.0 / (1.0 + t * t).sqrt();
+                let sn = cs * t;
+                for m in [&mut u, &mut v] {
+                    for row in m.iter_mut() {
+                        let (a, b) = (row[p], row[q]);
+                        row[p] = cs * a - sn * b;
+                        row[q] = sn * a + cs * b;
+                    }
+                }
+            }
+        }
+        if !rotated {
+            break;
+        }
+    }
+
+    // Singular values are the column norms of the rotated U.
+    let mut s = [0.0; N];
+    for (j, sv) in s.iter_mut().enumerate() {
+        *sv = u.iter().map(|row| row[j] * row[j]).sum::<f64>().sqrt();
+        if *sv > 1e-300 {
+            for row in u.iter_mut() {
+                row[j] /= *sv;
+            }
+        }
+    }
+
+    // Sort descending, permuting the columns of U and V alongside.
+    let mut order: Vec<usize> = (0..N).collect();
+    order.sort_by(|&a, &b| s[b].total_cmp(&s[a]));
+    let mut u_sorted = [[0.0; N]; N];
+    let mut v_sorted = [[0.0; N]; N];
+    let mut s_sorted = [0.0; N];
+    for (new_j, &old_j) in order.iter().enumerate() {
+        s_sorted[new_j] = s[old_j];
+        for i in 0..N {
+            u_sorted[i][new_j] = u[i][old_j];
+            v_sorted[i][new_j] = v[i][old_j];
+        }
+    }
+    (u_sorted, s_sorted, v_sorted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::{RngSource, XorShift64};
+
+    fn random_rows(rng: &mut XorShift64, rows: usize) -> Vec<[f64; N]> {
+        (0..rows).map(|_| std::array::from_fn(|_| rng.next_f32() as f64 - 0.5)).collect()
+    }
+
+    #[test]
+    fn qr_reconstructs_with_orthonormal_q() {
+        let mut rng = XorShift64::new(103);
+        let a = random_rows(&mut rng, 3 * N);
+        let mut q = a.clone();
+        let r = qr_in_place(&mut q);
+        for i in 0..N {
+            for j in 0..N {
+                let dot: f64 = q.iter().map(|row| row[i] * row[j]).sum();
+                assert!((dot - if i == j { 1.0 } else { 0.0 }).abs() < 1e-9);
+                if i > j {
+                    assert_eq!(r[i][j], 0.0);
+                }
+            }
+        }
+        for (row, q_row) in a.iter().zip(&q) {
+            for j in 0..N {
+                let v: f64 = (0..N).map(|m| q_row[m] * r[m][j]).sum();
+                assert!((v - row[j]).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn svd_reconstructs_with_sorted_values() {
+        let mut rng = XorShift64::new(104);
+        let mut c = [[0.0; N]; N];
+        c.copy_from_slice(&random_rows(&mut rng, N));
+        // A rank-deficient column must come back as a zero singular value
+        for row in c.iter_mut() {
+            row[N - 1] = row[0];
+        }
+        let (u, s, v) = svd(&c);
+        assert!(s.windows(2).all(|w| w[0] >= w[1]));
+        assert!(s[N - 1] < 1e-9);
+        for i in 0..N {
+            for j in 0..N {
+                let v_ij: f64 = (0..N).map(|k| u[i][k] * s[k] * v[j][k]).sum();
+                assert!((v_ij - c[i][j]).abs() < 1e-9);
+            }
+        }
+    }
+}
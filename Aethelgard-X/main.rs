@@ -1,9 +1,21 @@
+mod analysis;
+mod api;
+mod augment;
+mod bench;
 mod geometry_tables;
 mod cga;
+mod linalg;
+mod material;
+mod rng;
 mod shadow;
+mod tablebase;
+mod trace;
+mod tt;
+mod util;
 mod field;
 mod eval;
 mod engine;
+mod error;
 
 use std::io::{self, BufRead};
 use cozy_chess::*;
@@ -12,6 +24,7 @@ use engine::AethelgardX;
 fn main() {
     let mut engine = AethelgardX::new();
     let mut board = Board::default();
+    let mut game_hashes: Vec<u64> = Vec::new();
 
     let stdin = io::stdin();
     for line in stdin.lock().lines() {
@@ -27,15 +40,23 @@ fn main() {
             Some(&"isready") => println!("readyok"),
             Some(&"ucinewgame") => {
                 board = Board::default();
+                game_hashes.clear();
+                engine.shadow.tt.clear();
+                engine.plan = None;
             }
             Some(&"position") => {
                 if let Some(&"startpos") = parts.get(1) {
                     board = Board::default();
+                    game_hashes.clear();
                     if let Some(&"moves") = parts.get(2) {
                         for &mv_str in &parts[3..] {
-                            if let Ok(mv) = mv_str.parse::<Move>() {
-                                board.play(mv);
+                            let before = board.hash();
+                            if let Err(e) = api::play_moves(&mut board, &[mv_str]) {
+                                // Stay on the last legal position rather than guess
+                                println!("info string {}", e);
+                                break;
                             }
+                            game_hashes.push(before);
                         }
                     }
                 }
@@ -43,8 +64,22 @@ fn main() {
             Some(&"go") => {
                 engine.nodes = 0;
                 engine.board = board.clone();
-                let best_move = engine.get_best_move();
-                println!("bestmove {}", best_move);
+                engine.shadow.set_game_history(&game_hashes);
+                // `go depth N` runs the iterative deepening search with info lines
+                if let Some(depth) = parts.iter().position(|&p| p == "depth").and_then(|i| parts.get(i + 1)).and_then(|d| d.parse().ok()) {
+                    match engine.search_with_info(&board, depth, &mut io::stdout()) {
+                        (_, Some(best_move)) => println!("bestmove {}", best_move),
+                        (_, None) => println!("bestmove 0000"),
+                    }
+                    continue;
+                }
+                match engine.get_best_move() {
+                    Ok(best_move) => println!("bestmove {}", best_move),
+                    Err(e) => {
+                        println!("info string {}", e);
+                        println!("bestmove 0000");
+                    }
+                }
             }
             Some(&"quit") => break,
             _ => {}
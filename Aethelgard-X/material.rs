@@ -0,0 +1,223 @@
+use cozy_chess::*;
+use crate::util::captured_piece;
+
+/// Centipawn value of each piece type, indexed by `Piece as usize` (the king is
+/// never traded). The single source for material, search and capture ordering.
+pub const PIECE_VALUES: [i32; 6] = [100, 320, 330, 500, 900, 0];
+
+pub fn piece_value(piece: Piece) -> i32 {
+    PIECE_VALUES[piece as usize]
+}
+
+/// Phase weight of each piece type, indexed by `Piece as usize`: minors 1,
+/// rooks 2, queens 4, so the start position adds up to `OPENING_PHASE`.
+const PHASE_WEIGHTS: [i32; 6] = [0, 1, 1, 2, 4, 0];
+/// `game_phase` of the start position
+pub const OPENING_PHASE: i32 = 24;
+/// At or below this phase (a queen and a rook, say) `endgame_king_term` applies
+const ENDGAME_PHASE: i32 = 6;
+/// Bonus of a passed pawn by its rank relative to its owner (ranks 1 and 8
+/// never hold a pawn), in the middlegame; up to double with no pieces left
+const PASSED_PAWN_BONUS: [i32; 8] = [0, 5, 10, 20, 35, 60, 100, 0];
+/// Material lead, in centipawns, from which the leader is trying to mate
+const MOP_UP_MIN_ADVANTAGE: i32 = 300;
+/// Bonus per step the losing king stands from the center
+const MOP_UP_EDGE_WEIGHT: i32 = 10;
+/// Bonus per step the kings are closer than the full board apart
+const MOP_UP_PROXIMITY_WEIGHT: i32 = 4;
+
+/// Material balance (White minus Black) carried through the search so that
+/// nodes update it on captures/promotions instead of recounting bitboards.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Material {
+    pub balance: i32,
+}
+
+impl Material {
+    /// Full recount from the piece bitboards.
+    pub fn from_board(board: &Board) -> Self {
+        let mut balance = 0;
+        for piece in Piece::ALL {
+            let value = piece_value(piece);
+            balance += board.colored_pieces(Color::White, piece).len() as i32 * value;
+            balance -= board.colored_pieces(Color::Black, piece).len() as i32 * value;
+        }
+        Self { balance }
+    }
+
+    /// Balance after `mv` is played on `board` (the position before the move).
+    pub fn after_move(self, board: &Board, mv: Move) -> Self {
+        let us = board.side_to_move();
+        let sign = if us == Color::White { 1 } else { -1 };
+        let mut delta = 0;
+
+        if let Some(victim) = captured_piece(board, mv) {
+            delta += piece_value(victim);
+        }
+        if let Some(promo) = mv.promotion {
+            delta += piece_value(promo) - piece_value(Piece::Pawn);
+        }
+
+        Self { balance: self.balance + sign * delta }
+    }
+
+    /// Balance from `color`'s point of view.
+    pub fn relative(self, color: Color) -> i32 {
+        if color == Color::White { self.balance } else { -self.balance }
+    }
+}
+
+/// Dead draw by material: no pawns, rooks or queens, and at most one minor
+/// piece, or only bishops that all stand on one square color.
+pub fn is_insufficient_material(board: &Board) -> bool {
+    let heavy = board.pieces(Piece::Pawn) | board.pieces(Piece::Rook) | board.pieces(Piece::Queen);
+    if !heavy.is_empty() {
+        return false;
+    }
+    let knights = board.pieces(Piece::Knight);
+    let bishops = board.pieces(Piece::Bishop);
+    if knights.len() + bishops.len() <= 1 {
+        return true;
+    }
+    knights.is_empty() && ((bishops & BitBoard::DARK_SQUARES).is_empty() || (bishops & BitBoard::LIGHT_SQUARES).is_empty())
+}
+
+/// Non-pawn material of both sides in phase units (see `PHASE_WEIGHTS`):
+/// `OPENING_PHASE` with every piece on, 0 with only kings and pawns.
+pub fn game_phase(board: &Board) -> i32 {
+    Piece::ALL.iter().map(|&piece| board.pieces(piece).len() as i32 * PHASE_WEIGHTS[piece as usize]).sum()
+}
+
+/// Mop-up knowledge for won endgames, side-to-move relative. Once the phase is
+/// at most `ENDGAME_PHASE` and one side leads by `MOP_UP_MIN_ADVANTAGE`, the
+/// leader earns the losing king's distance from the center (steps in file plus
+/// rank, 0 to 6) and the closeness of the two kings (7 minus their Chebyshev
+/// distance): the mating side drives the king to the edge and brings its own up.
+pub fn endgame_king_term(board: &Board) -> i32 {
+    if game_phase(board) > ENDGAME_PHASE {
+        return 0;
+    }
+    let balance = Material::from_board(board).balance;
+    let winner = match balance {
+        b if b >= MOP_UP_MIN_ADVANTAGE => Color::White,
+        b if b <= -MOP_UP_MIN_ADVANTAGE => Color::Black,
+        _ => return 0,
+    };
+    let (attacker, defender) = (board.king(winner), board.king(!winner));
+    let (file, rank) = (defender.file() as i32, defender.rank() as i32);
+    let edge = (3 - file).max(file - 4) + (3 - rank).max(rank - 4);
+    let distance = (attacker.file() as i32 - file).abs().max((attacker.rank() as i32 - rank).abs());
+    let score = MOP_UP_EDGE_WEIGHT * edge + MOP_UP_PROXIMITY_WEIGHT * (7 - distance);
+    if winner == board.side_to_move() { score } else { -score }
+}
+
+/// Pawns of `color` with no enemy pawn ahead of them on their own or an
+/// adjacent file, so nothing but pieces can stop them queening.
+pub fn passed_pawns(board: &Board, color: Color) -> BitBoard {
+    let enemy_pawns = board.colored_pieces(!color, Piece::Pawn);
+    let mut passed = BitBoard::EMPTY;
+    for pawn in board.colored_pieces(color, Piece::Pawn) {
+        let rank = pawn.rank().relative_to(color) as usize;
+        let ahead = Rank::ALL
+            .into_iter()
+            .filter(|r| r.relative_to(color) as usize > rank)
+            .fold(BitBoard::EMPTY, |bb, r| bb | r.bitboard());
+        let files = pawn.file().bitboard() | pawn.file().adjacent();
+        if (enemy_pawns & files & ahead).is_empty() {
+            passed |= pawn.bitboard();
+        }
+    }
+    passed
+}
+
+/// Passed pawns of the side to move minus the opponent's, each worth
+/// `PASSED_PAWN_BONUS` for its rank, scaled from 1x at `OPENING_PHASE` up to
+/// 2x once `game_phase` reaches 0.
+pub fn passed_pawn_score(board: &Board) -> i32 {
+    let side_score = |color: Color| -> i32 {
+        passed_pawns(board, color)
+            .into_iter()
+            .map(|pawn| PASSED_PAWN_BONUS[pawn.rank().relative_to(color) as usize])
+            .sum()
+    };
+    let us = board.side_to_move();
+    let raw = side_score(us) - side_score(!us);
+    let phase = game_phase(board).min(OPENING_PHASE);
+    raw * (2 * OPENING_PHASE - phase) / OPENING_PHASE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bench::random_positions;
+
+    /// Checks the incremental balance against a recount for every legal move.
+    fn assert_incremental_matches_recount(board: &Board) {
+        let material = Material::from_board(board);
+        board.generate_moves(|mvs| {
+            for mv in mvs {
+                let mut next = board.clone();
+                next.play(mv);
+                assert_eq!(material.after_move(board, mv), Material::from_board(&next), "{} {}", board, mv);
+            }
+            false
+        });
+    }
+
+    #[test]
+    fn incremental_balance_matches_recount() {
+        // En passant, promotions with and without capture, castling
+        for fen in [
+            "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 2",
+            "1r2k3/P7/8/8/8/8/7p/4K1N1 w - - 0 1",
+            "1r2k3/P7/8/8/8/8/7p/4K1N1 b - - 0 1",
+            "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1",
+        ] {
+            assert_incremental_matches_recount(&fen.parse().unwrap());
+        }
+        for board in random_positions(107, 100, 60) {
+            assert_incremental_matches_recount(&board);
+        }
+    }
+
+    #[test]
+    fn insufficient_material_table() {
+        for (fen, dead) in [
+            ("4k3/8/8/8/8/8/8/4K3 w - - 0 1", true),
+            ("4k3/8/8/8/8/8/8/1N2K3 w - - 0 1", true),
+            // c1 and f8 are both dark squares
+            ("4kb2/8/8/8/8/8/8/2B1K3 w - - 0 1", true),
+            ("2b1k3/8/8/8/8/8/8/2B1K3 w - - 0 1", false),
+            ("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1", false),
+            ("4k3/8/8/8/8/8/P7/4K3 w - - 0 1", false),
+        ] {
+            assert_eq!(is_insufficient_material(&fen.parse().unwrap()), dead, "{}", fen);
+        }
+    }
+
+    #[test]
+    fn adjacent_file_pawns_stop_a_passer() {
+        // e5 and d6 guard each other's path; a5 and h7 run free
+        let board: Board = "4k3/7p/3p4/P3P3/8/8/8/4K3 w - - 0 1".parse().unwrap();
+        assert_eq!(passed_pawns(&board, Color::White), Square::A5.bitboard());
+        assert_eq!(passed_pawns(&board, Color::Black), Square::H7.bitboard());
+        // A pawn behind doesn't stop one ahead of it
+        let behind: Board = "4k3/8/8/4P3/3p4/8/8/4K3 w - - 0 1".parse().unwrap();
+        assert_eq!(passed_pawns(&behind, Color::White), Square::E5.bitboard());
+    }
+
+    #[test]
+    fn mop_up_rewards_the_cornered_king() {
+        let scores: Vec<i32> = ["8/8/8/4k3/8/8/8/1Q2K3", "8/8/4k3/8/8/8/8/1Q2K3", "8/4k3/8/8/8/8/8/1Q2K3", "4k3/8/8/8/8/8/8/1Q2K3"]
+            .iter()
+            .map(|placement| endgame_king_term(&format!("{} w - - 0 1", placement).parse().unwrap()))
+            .collect();
+        assert!(scores.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", scores);
+        // The defender sees the same term from the other side
+        let defending: Board = "4k3/8/8/8/8/8/8/1Q2K3 b - - 0 1".parse().unwrap();
+        assert_eq!(endgame_king_term(&defending), -scores[3]);
+        // Not without a decisive lead
+        let level: Board = "4k2r/8/8/8/8/8/8/1R2K3 w - - 0 1".parse().unwrap();
+        assert_eq!(endgame_king_term(&level), 0);
+    }
+}
@@ -1,16 +1,247 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::io::Write;
+use std::sync::Arc;
 use cozy_chess::*;
+use crate::material::{endgame_king_term, is_insufficient_material, passed_pawn_score, Material};
+use crate::util::{has_winning_capture, is_capture, mvv_lva};
+use crate::tablebase::{NoTablebase, Tablebase, Wdl};
+use crate::tt::{Bound, PathHistory, TranspositionTable};
+use crate::rng::{RngSource, XorShift64};
+use crate::trace::TreeRecorder;
+
+/// Nodes between polls of the stop flag
+const STOP_POLL_INTERVAL: u64 = 1024;
+/// Mate score at the root; a mate `ply` half-moves away scores MATE_SCORE - ply
+pub const MATE_SCORE: i32 = 20000;
+/// Tablebase wins rank below real mates but above any heuristic eval
+const TB_WIN_SCORE: i32 = 15000;
+/// Default cap on quiescence recursion, in plies past the horizon
+pub const DEFAULT_QDEPTH: i32 = 8;
+/// Default hard cap on search plies from the root, quiescence included
+pub const DEFAULT_MAX_PLY: i32 = 128;
+/// Default share of the root material balance that draws are penalized by
+pub const DEFAULT_CONTEMPT_FACTOR: f32 = 0.1;
+/// Largest contempt, in centipawns, either way
+const MAX_CONTEMPT: i32 = 50;
+/// Plies without a capture or pawn move after which the game is drawn
+const FIFTY_MOVE_PLIES: u8 = 100;
+/// Deepest iteration `search_nodes` will start
+const MAX_ITERATIVE_DEPTH: i32 = 64;
+/// Smallest advantage (centipawns, either side) `detect_fortress` considers
+const FORTRESS_MIN_ADVANTAGE: i32 = 100;
+/// Largest score spread across depths that still counts as "unchanging"
+const FORTRESS_SCORE_DRIFT: i32 = 10;
+
+/// Score for a side to move with no legal moves: mated if in check, stalemate (draw) otherwise.
+pub fn terminal_score(board: &Board, ply: i32) -> i32 {
+    if board.checkers().is_empty() { 0 } else { -(MATE_SCORE - ply) }
+}
+
+/// Picks from `moves` (best first) among those within `margin` of the best,
+/// with probability proportional to `exp((score - best) / temperature)`.
+/// A temperature of 0 (or less) returns the best move.
+pub fn sample_near_best(moves: &[(Move, i32)], temperature: f32, margin: i32, rng: &mut (impl RngSource + ?Sized)) -> (Move, i32) {
+    let (best_move, best) = moves[0];
+    if temperature <= 0.0 {
+        return (best_move, best);
+    }
+    let weights: Vec<f32> = moves
+        .iter()
+        .take_while(|&&(_, score)| score >= best - margin)
+        .map(|&(_, score)| ((score - best) as f32 / temperature).exp())
+        .collect();
+    let mut draw = rng.next_f32() * weights.iter().sum::<f32>();
+    for (i, weight) in weights.iter().enumerate() {
+        if draw < *weight {
+            return moves[i];
+        }
+        draw -= weight;
+    }
+    // Rounding left the draw past the last weight
+    moves[weights.len() - 1]
+}
 
 pub struct ShadowGuard {
+    /// Nodes searched since the current top-level search started
     pub nodes: u64,
+    /// Cooperative cancellation: set from another thread (e.g. UCI `stop`)
+    /// to make the running search unwind with its best move so far. Each
+    /// top-level search (`search_with_move`, `search_nodes`, `resume`,
+    /// `search_iterations`, `adjudicate`) clears it when it starts, so a stop
+    /// only ends the search it hit.
+    pub stop: Arc<AtomicBool>,
+    stopped: bool,
+    /// Searches unwind like a `stop` once `nodes` reaches this
+    pub node_limit: u64,
+    pub tablebase: Box<dyn Tablebase>,
+    /// Positions with at most this many pieces are answered by the tablebase
+    pub tb_pieces: u32,
+    /// Quiescence plies allowed before falling back to stand pat
+    pub qdepth: i32,
+    /// Nodes this many plies from the root return the static eval instead of
+    /// recursing, whatever the depth or `qdepth`, so the stack stays bounded.
+    /// Keep it below `tt::MAX_PLY`, where mate scores stop being recognized.
+    pub max_ply: i32,
+    /// Deepest ply reached by the last search, quiescence included
+    pub seldepth: i32,
+    /// Contempt per centipawn of root material advantage (see `contempt_for`)
+    pub contempt_factor: f32,
+    /// Contempt of the current search, for the root side to move
+    contempt: i32,
+    /// Weight of `passed_pawn_score` in the static eval (0 = off)
+    pub passed_pawn_weight: f32,
+    pub tt: TranspositionTable,
+    /// Current line for repetition detection. Repetitions are checked before the
+    /// TT so a cached score never masks a draw, and any node whose subtree hit a
+    /// repetition is not stored, since that draw depends on the path, not the position.
+    pub history: PathHistory,
+    /// Draws scored so far whose value the TT can't hold: repetition and
+    /// fifty-move draws (neither is in the hash) and, while contempt is on,
+    /// any draw, since its score depends on the root
+    path_draws: u64,
+    /// Training-data sink: one `fen,eval,score` CSV line per top-level search
+    logger: Option<Box<dyn Write>>,
+    pub adjudicator: Adjudicator,
+    /// Root moves of the last completed iterative-deepening iteration
+    pub last_iteration: Option<RootOrder>,
+    /// Root moves to try before the others (e.g. `field::geometric_moves`);
+    /// ignored where they aren't legal. A stored iteration still comes first.
+    pub root_hints: Vec<Move>,
+    /// Scores of the root moves finished so far in the current root search
+    root_scores: Vec<(Move, i32)>,
+    /// Softmax temperature, in centipawns, for picking among near-equal root
+    /// moves after iterative deepening; 0 always plays the best move.
+    pub temperature: f32,
+    /// Root moves this many centipawns below the best are never sampled. While
+    /// the temperature is on, the root window is widened by it so their scores are exact.
+    pub temperature_margin: i32,
+    /// Sampling source; reseed it (or swap in a stub) for reproducible games
+    pub rng: Box<dyn RngSource>,
+    /// Search-tree recorder for debugging; None (the default) records nothing.
+    /// Each root search replaces the tree of the previous one; the internal
+    /// searches of tactical probes and analyses are not recorded.
+    pub recorder: Option<TreeRecorder>,
+}
+
+/// Root move ordering kept from a completed iteration, so a later search of
+/// the same position can start from it (see `ShadowGuard::resume`).
+#[derive(Clone, Debug)]
+pub struct RootOrder {
+    pub hash: u64,
+    pub depth: i32,
+    /// Best first; scores of moves after the best are fail-hard upper bounds,
+    /// except within `temperature_margin` of it while sampling is on
+    pub moves: Vec<(Move, i32)>,
+}
+
+/// Resign/draw decision for automated play.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Adjudication {
+    Resign,
+    OfferDraw,
+    Play,
+}
+
+/// Thresholds for `ShadowGuard::adjudicate`. A verdict needs `sustain`
+/// consecutive adjudicated scores past its threshold, so one-move blips don't count.
+pub struct Adjudicator {
+    /// Resign once the score (side to move) stays at or below this
+    pub resign_score: i32,
+    /// Offer a draw once |score| stays within this...
+    pub draw_margin: i32,
+    /// ...and no capture or pawn move has been made for this many plies
+    pub draw_quiet_plies: u8,
+    pub sustain: usize,
+    recent: Vec<i32>,
+}
+
+impl Default for Adjudicator {
+    fn default() -> Self {
+        Self { resign_score: -800, draw_margin: 10, draw_quiet_plies: 20, sustain: 3, recent: Vec::new() }
+    }
+}
+
+impl Adjudicator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a score and returns the verdict over the last `sustain` scores.
+    pub fn record(&mut self, score: i32, halfmove_clock: u8) -> Adjudication {
+        self.recent.push(score);
+        if self.recent.len() > self.sustain {
+            self.recent.remove(0);
+        }
+        if self.recent.len() < self.sustain {
+            return Adjudication::Play;
+        }
+        if self.recent.iter().all(|&s| s <= self.resign_score) {
+            Adjudication::Resign
+        } else if halfmove_clock >= self.draw_quiet_plies && self.recent.iter().all(|&s| s.abs() <= self.draw_margin) {
+            Adjudication::OfferDraw
+        } else {
+            Adjudication::Play
+        }
+    }
+
+    /// Forgets recorded scores (e.g. on `ucinewgame`).
+    pub fn reset(&mut self) {
+        self.recent.clear();
+    }
 }
 
 pub struct TacticalFeedback {
     pub is_safe: bool,
     pub danger_squares: Vec<(usize, f32)>,
+    /// Search score of the move for the side making it, after the best reply
+    pub score: i32,
 }
 impl ShadowGuard {
     pub fn new() -> Self {
-        Self { nodes: 0 }
+        Self {
+            nodes: 0,
+            stop: Arc::new(AtomicBool::new(false)),
+            stopped: false,
+            node_limit: u64::MAX,
+            tablebase: Box::new(NoTablebase),
+            tb_pieces: 0,
+            qdepth: DEFAULT_QDEPTH,
+            max_ply: DEFAULT_MAX_PLY,
+            seldepth: 0,
+            contempt_factor: DEFAULT_CONTEMPT_FACTOR,
+            contempt: 0,
+            passed_pawn_weight: 1.0,
+            tt: TranspositionTable::new(16),
+            history: PathHistory::new(),
+            path_draws: 0,
+            logger: None,
+            adjudicator: Adjudicator::new(),
+            last_iteration: None,
+            root_hints: Vec::new(),
+            root_scores: Vec::new(),
+            temperature: 0.0,
+            temperature_margin: 20,
+            rng: Box::new(XorShift64::new(0)),
+            recorder: None,
+        }
+    }
+
+    /// Logs every top-level search (one line per decision, not per iteration)
+    /// as `fen,eval,score` (static eval and search score, both from the side
+    /// to move) for building Texel/DMRG datasets.
+    pub fn with_logger(mut self, writer: impl Write + 'static) -> Self {
+        self.logger = Some(Box::new(writer));
+        self
+    }
+
+    fn log_root(&mut self, board: &Board, score: i32) {
+        let eval = self.eval(board, Material::from_board(board));
+        if let Some(writer) = self.logger.as_mut() {
+            if writeln!(writer, "{},{},{}", board, eval, score).is_err() {
+                // A broken sink shouldn't fail the search; stop logging instead
+                self.logger = None;
+            }
+        }
     }
 
     /// The Veto Protocol: Checks if a manifold move is tactically "insane"
@@ -19,15 +250,36 @@ impl ShadowGuard {
     }
 
     pub fn probe_tactics(&mut self, board: &Board, mv: Move) -> TacticalFeedback {
+        // Stale or illegal moves (e.g. from another position) are vetoed, never played
+        if !board.is_legal(mv) {
+            return TacticalFeedback { is_safe: false, danger_squares: Vec::new(), score: -MATE_SCORE };
+        }
+
         let mut next_board = board.clone();
         next_board.play(mv);
 
+        // `status` reports for the game, not the side to move: `Won` means the
+        // side to move is checkmated. After our legal move that side is the
+        // opponent (we can't be left in check), so this is our mate
         if next_board.status() == GameStatus::Won {
-            return TacticalFeedback { is_safe: true, danger_squares: Vec::new() };
+            return TacticalFeedback { is_safe: true, danger_squares: Vec::new(), score: MATE_SCORE - 1 };
         }
-        
+
+        // Pre-screen: a quiet, non-checking move that leaves nothing hanging (no
+        // reply capture wins material by SEE) and allows no mate in one is
+        // passed without the probe search and scored by static material
+        if !is_capture(board, mv)
+            && next_board.checkers().is_empty()
+            && !has_winning_capture(&next_board, 1)
+            && !has_mate_in_one(&next_board)
+        {
+            let score = -self.eval(&next_board, Material::from_board(&next_board));
+            return TacticalFeedback { is_safe: true, danger_squares: Vec::new(), score };
+        }
+
         // Find the opponent's best response
-        let (score, best_response) = self.search_with_move(&next_board, 4, -30000, 30000);
+        // Internal probe: not a root search, so it stays out of the training log
+        let (score, best_response) = self.probe_root(&next_board, 4, -30000, 30000);
         
         let is_safe = -score > -50;
         let mut danger_squares = Vec::new();
@@ -38,19 +290,396 @@ impl ShadowGuard {
             if let Some(res_mv) = best_response {
                 danger_squares.push((res_mv.to as usize, 1000.0));
                 // Also mark the 'from' square as dangerous if it was a capture
-                if board.color_on(res_mv.to).is_some() {
+                if is_capture(&next_board, res_mv) {
                     danger_squares.push((res_mv.from as usize, 500.0));
                 }
             }
         }
 
-        TacticalFeedback { is_safe, danger_squares }
+        TacticalFeedback { is_safe, danger_squares, score: -score }
+    }
+
+    /// What the opponent threatens if it were their move: after a null move,
+    /// each of their moves is searched `depth` plies deep (at least 1), and
+    /// every move gaining over their static eval marks its destination with the
+    /// gain in centipawns (the best move per square), strongest first. Empty
+    /// in check, where the threat is already on the board.
+    pub fn threat_map(&mut self, board: &Board, depth: i32) -> Vec<(usize, f32)> {
+        let passed = match board.null_move() {
+            Some(passed) => passed,
+            None => return Vec::new(),
+        };
+        let baseline = self.eval(&passed, Material::from_board(&passed));
+        let mut moves = Vec::new();
+        passed.generate_moves(|mvs| {
+            moves.extend(mvs);
+            false
+        });
+
+        let mut threats = [0.0f32; 64];
+        for mv in moves {
+            let mut next_board = passed.clone();
+            next_board.play(mv);
+            let (score, _) = self.probe_root(&next_board, depth.max(1) - 1, -30000, 30000);
+            let gain = (-score - baseline) as f32;
+            let slot = &mut threats[mv.to as usize];
+            *slot = slot.max(gain);
+        }
+        let mut map: Vec<(usize, f32)> = threats.iter().enumerate().filter(|&(_, &g)| g > 0.0).map(|(sq, &g)| (sq, g)).collect();
+        map.sort_by(|a, b| b.1.total_cmp(&a.1));
+        map
+    }
+
+    /// Searches `board` and feeds the score to the adjudicator. Call once per
+    /// own move; the verdict only changes after `adjudicator.sustain` calls agree.
+    /// A top-level search like the others, but not recorded or logged.
+    pub fn adjudicate(&mut self, board: &Board, depth: i32) -> Adjudication {
+        self.begin_search();
+        let (score, _) = self.probe_root(board, depth, -30000, 30000);
+        self.adjudicator.record(score, board.halfmove_clock())
+    }
+
+    /// Fortress signature: searching depths 1..=`plies`, the score is at least
+    /// `FORTRESS_MIN_ADVANTAGE` either way but stays flat over the deeper half of
+    /// the iterations, and the final principal variation (followed through the
+    /// TT) neither captures, promotes nor moves a pawn. Such an advantage is real
+    /// on the board but unconvertible, which the adjudicator can't see.
+    /// Pawnless positions never qualify: there the advantage is converted by
+    /// mating, often beyond any horizon, not by changing the structure.
+    pub fn detect_fortress(&mut self, board: &Board, plies: usize) -> bool {
+        if board.pieces(Piece::Pawn).is_empty() {
+            return false;
+        }
+        let plies = plies.max(2) as i32;
+        let mut scores = Vec::with_capacity(plies as usize);
+        for depth in 1..=plies {
+            let (score, _) = self.probe_root(board, depth, -30000, 30000);
+            scores.push(score);
+        }
+        let settled = &scores[(plies / 2) as usize..];
+        let (low, high) = (settled.iter().min().unwrap(), settled.iter().max().unwrap());
+        if high - low > FORTRESS_SCORE_DRIFT || settled.iter().any(|s| s.abs() < FORTRESS_MIN_ADVANTAGE) {
+            return false;
+        }
+
+        let pieces = board.occupied().len();
+        let pawns = board.pieces(Piece::Pawn);
+        let mut line = board.clone();
+        for mv in self.principal_variation(board, plies as usize) {
+            line.play(mv);
+            if line.occupied().len() != pieces || line.pieces(Piece::Pawn) != pawns {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Best line from `board` as left in the TT by the last search, up to
+    /// `max_len` moves. Stops early at a missing or overwritten entry.
+    pub fn principal_variation(&self, board: &Board, max_len: usize) -> Vec<Move> {
+        let mut line = board.clone();
+        let mut pv = Vec::new();
+        while pv.len() < max_len {
+            match self.tt.get(line.hash()).and_then(|e| e.best_move) {
+                Some(mv) if line.is_legal(mv) => {
+                    line.play(mv);
+                    pv.push(mv);
+                }
+                _ => break,
+            }
+        }
+        pv
+    }
+
+    /// Best move by a `depth`-ply search that also passes the tactical veto.
+    /// Root moves are tried in score order; if every one is vetoed the top
+    /// move is returned anyway, so this is only None when there are no moves.
+    pub fn best_safe_move(&mut self, board: &Board, depth: i32) -> Option<Move> {
+        let mut moves = Vec::new();
+        board.generate_moves(|mvs| {
+            moves.extend(mvs);
+            false
+        });
+
+        let mut ranked = Vec::with_capacity(moves.len());
+        for mv in moves {
+            let mut next_board = board.clone();
+            next_board.play(mv);
+            let (score, _) = self.probe_root(&next_board, (depth - 1).max(0), -30000, 30000);
+            ranked.push((-score, mv));
+        }
+        ranked.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+
+        for &(_, mv) in &ranked {
+            if self.verify_move(board, mv) {
+                return Some(mv);
+            }
+        }
+        ranked.first().map(|&(_, mv)| mv)
+    }
+
+    /// Positions played before the root (oldest first), so the search sees
+    /// repetitions of the game and not just of its own line.
+    pub fn set_game_history(&mut self, hashes: &[u64]) {
+        self.history.set_game(hashes);
+    }
+
+    pub fn search_with_move(&mut self, board: &Board, depth: i32, alpha: i32, beta: i32) -> (i32, Option<Move>) {
+        self.begin_search();
+        let (score, best) = self.search_root(board, depth, alpha, beta);
+        if self.stopped && best.is_none() {
+            // Interrupted before any root move finished: still answer with a legal move
+            let mut first = None;
+            board.generate_moves(|mvs| {
+                first = mvs.into_iter().next();
+                first.is_some()
+            });
+            self.log_root(board, score);
+            return (score, first);
+        }
+        self.log_root(board, score);
+        (score, best)
+    }
+
+    /// Iterative deepening that stops once `max_nodes` nodes have been searched.
+    /// Returns the last completed depth's result, or the interrupted iteration's
+    /// root best when that iteration already found one.
+    pub fn search_nodes(&mut self, board: &Board, max_nodes: u64) -> (i32, Option<Move>) {
+        let saved_limit = self.node_limit;
+        self.begin_search();
+        self.node_limit = max_nodes;
+        let result = self.deepen(board, 1, MAX_ITERATIVE_DEPTH, &mut |_, _, _| {});
+        self.node_limit = saved_limit;
+        result
+    }
+
+    /// Continues iterative deepening from `last_iteration` (when it is for this
+    /// position) for `extra_depth` more plies, starting from its root ordering
+    /// instead of redoing the shallow iterations. Otherwise searches from depth 1.
+    pub fn resume(&mut self, board: &Board, extra_depth: i32) -> (i32, Option<Move>) {
+        let done = match &self.last_iteration {
+            Some(order) if order.hash == board.hash() => order.depth,
+            _ => 0,
+        };
+        self.begin_search();
+        self.deepen(board, done + 1, done + extra_depth.max(1), &mut |_, _, _| {})
+    }
+
+    /// Iterative deepening over depths `1..=depth` that calls `on_iteration`
+    /// with the depth and result of each completed iteration, e.g. for UCI
+    /// `info` lines. Like every top-level search it logs only its final result.
+    pub fn search_iterations(
+        &mut self,
+        board: &Board,
+        depth: i32,
+        mut on_iteration: impl FnMut(&Self, i32, (i32, Option<Move>)),
+    ) -> (i32, Option<Move>) {
+        self.begin_search();
+        self.deepen(board, 1, depth.max(1), &mut on_iteration)
+    }
+
+    /// Start of a top-level search: drops a stop request left from the last
+    /// one and restarts the node count.
+    fn begin_search(&mut self) {
+        self.stop.store(false, Ordering::Relaxed);
+        self.nodes = 0;
+    }
+
+    /// Iterations `first..=last`; an interrupted iteration's root best is used
+    /// when it found one, else the last completed (or stored) iteration's.
+    fn deepen(
+        &mut self,
+        board: &Board,
+        first: i32,
+        last: i32,
+        on_iteration: &mut impl FnMut(&Self, i32, (i32, Option<Move>)),
+    ) -> (i32, Option<Move>) {
+        let mut result = match &self.last_iteration {
+            Some(order) if order.hash == board.hash() => order.moves.first().map_or((0, None), |&(mv, score)| (score, Some(mv))),
+            _ => (0, None),
+        };
+        for depth in first..=last {
+            let (score, best) = self.search_root(board, depth, -30000, 30000);
+            if self.stopped {
+                if best.is_some() {
+                    result = (score, best);
+                }
+                break;
+            }
+            result = (score, best);
+            if !self.root_scores.is_empty() {
+                let mut moves = std::mem::take(&mut self.root_scores);
+                // Stable, so equal scores keep the canonical root order
+                moves.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+                self.last_iteration = Some(RootOrder { hash: board.hash(), depth, moves });
+            }
+            on_iteration(self, depth, result);
+        }
+        if self.temperature > 0.0 {
+            if let Some(order) = self.last_iteration.as_ref().filter(|o| o.hash == board.hash()) {
+                // Only when the stored iteration is the one that chose the move
+                if order.moves.first().map(|&(mv, _)| mv) == result.1 {
+                    let (mv, score) = sample_near_best(&order.moves, self.temperature, self.temperature_margin, &mut *self.rng);
+                    result = (score, Some(mv));
+                }
+            }
+        }
+        self.log_root(board, result.0);
+
+        if result.1.is_none() {
+            // Budget too small to finish a single root move
+            board.generate_moves(|mvs| {
+                result.1 = mvs.into_iter().next();
+                result.1.is_some()
+            });
+        }
+        result
+    }
+
+    /// Root search without the legal-move fallback: `best` is None if interrupted
+    /// before a root move completed.
+    fn search_root(&mut self, board: &Board, depth: i32, alpha: i32, beta: i32) -> (i32, Option<Move>) {
+        self.stopped = false;
+        self.seldepth = 0;
+        self.contempt = self.contempt_for(board);
+        self.history.reset_path();
+        self.root_scores.clear();
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.start(depth, alpha, beta);
+        }
+        let result = match self.tablebase_root(board) {
+            Some((score, mv)) => (score, Some(mv)),
+            None => self.search_node(board, Material::from_board(board), depth, 0, alpha, beta),
+        };
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.exit(result.0);
+        }
+        result
+    }
+
+    /// `search_root` for the internal searches of probes and analyses
+    /// (`probe_tactics`, `threat_map`, `detect_fortress`, `best_safe_move`,
+    /// `adjudicate`):
+    /// the recorder is set aside, so it keeps the tree of the last real search.
+    fn probe_root(&mut self, board: &Board, depth: i32, alpha: i32, beta: i32) -> (i32, Option<Move>) {
+        let recorder = self.recorder.take();
+        let result = self.search_root(board, depth, alpha, beta);
+        self.recorder = recorder;
+        result
+    }
+
+    /// How much the side to move of `board` dislikes a draw when searching from
+    /// it: `contempt_factor` times its material balance, capped at
+    /// `MAX_CONTEMPT` either way. Ahead it pushes to convert; behind it is
+    /// negative, so a draw becomes welcome. Draws in the search then score
+    /// `-contempt` for the root side and `+contempt` for its opponent.
+    pub fn contempt_for(&self, board: &Board) -> i32 {
+        let balance = Material::from_board(board).relative(board.side_to_move());
+        ((balance as f32 * self.contempt_factor) as i32).clamp(-MAX_CONTEMPT, MAX_CONTEMPT)
+    }
+
+    /// Score of a draw for the side to move `ply` plies below the root.
+    fn draw_score(&self, ply: i32) -> i32 {
+        if ply & 1 == 0 { -self.contempt } else { self.contempt }
+    }
+
+    /// `draw_score` for a draw of the position itself (stalemate, insufficient
+    /// material). With contempt it still depends on the root, so it is counted
+    /// in `path_draws` to keep the nodes above it out of the TT.
+    fn position_draw(&mut self, ply: i32) -> i32 {
+        if self.contempt != 0 {
+            self.path_draws += 1;
+        }
+        self.draw_score(ply)
+    }
+
+    fn tablebase_score(&self, board: &Board) -> Option<i32> {
+        if board.occupied().len() > self.tb_pieces {
+            return None;
+        }
+        self.tablebase.probe_wdl(board).map(wdl_score)
+    }
+
+    /// Picks the root move straight from the tablebase: best WDL for us, then the
+    /// fastest zeroing when winning (slowest when losing). None if any child is missing.
+    fn tablebase_root(&self, board: &Board) -> Option<(i32, Move)> {
+        if board.occupied().len() > self.tb_pieces {
+            return None;
+        }
+        let mut moves = Vec::new();
+        board.generate_moves(|mvs| {
+            moves.extend(mvs);
+            false
+        });
+
+        let mut best: Option<(Wdl, i32, Move)> = None;
+        for mv in moves {
+            let mut next_board = board.clone();
+            next_board.play(mv);
+            let wdl = self.tablebase.probe_wdl(&next_board)?.flip();
+            let dtz = self.tablebase.probe_dtz(&next_board).unwrap_or(0).abs();
+            let key = if wdl == Wdl::Win { -dtz } else { dtz };
+            if best.is_none_or(|(bw, bk, _)| (wdl, key) > (bw, bk)) {
+                best = Some((wdl, key, mv));
+            }
+        }
+        let (wdl, _, mv) = best?;
+        Some((wdl_score(wdl), mv))
+    }
+
+    /// Handle for aborting searches from another thread.
+    pub fn stop_handle(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.stop)
     }
 
-    pub fn search_with_move(&mut self, board: &Board, depth: i32, mut alpha: i32, beta: i32) -> (i32, Option<Move>) {
+    /// True once the stop flag has been observed (polled every `STOP_POLL_INTERVAL`
+    /// nodes) or the node limit has been reached.
+    fn should_stop(&mut self) -> bool {
+        if !self.stopped
+            && (self.nodes >= self.node_limit
+                || (self.nodes.is_multiple_of(STOP_POLL_INTERVAL) && self.stop.load(Ordering::Relaxed)))
+        {
+            self.stopped = true;
+        }
+        self.stopped
+    }
+
+    /// Alpha-beta node; `material` is updated incrementally along the line.
+    fn search_node(&mut self, board: &Board, material: Material, depth: i32, ply: i32, mut alpha: i32, beta: i32) -> (i32, Option<Move>) {
         self.nodes += 1;
+        if self.should_stop() {
+            return (alpha, None);
+        }
+        self.seldepth = self.seldepth.max(ply);
+        if ply >= self.max_ply {
+            return (self.eval(board, material), None);
+        }
+        let hash = board.hash();
+        if ply > 0 && self.history.is_repetition(hash, board.halfmove_clock()) {
+            self.path_draws += 1;
+            return (self.draw_score(ply), None);
+        }
+        // Fifty-move rule, unless the move that reached 100 plies gave mate
+        if ply > 0 && board.halfmove_clock() >= FIFTY_MOVE_PLIES {
+            if !board.checkers().is_empty() && !board.generate_moves(|_| true) {
+                return (terminal_score(board, ply), None);
+            }
+            self.path_draws += 1;
+            return (self.draw_score(ply), None);
+        }
+        if ply > 0 && is_insufficient_material(board) {
+            return (self.position_draw(ply), None);
+        }
+        if let Some(score) = self.tablebase_score(board) {
+            return (score, None);
+        }
         if depth == 0 {
-            return (self.quiescence(board, alpha, beta), None);
+            return (self.quiescence(board, material, self.qdepth, ply, alpha, beta), None);
+        }
+        if ply > 0 {
+            if let Some(score) = self.tt.probe(hash, depth, alpha, beta, ply) {
+                return (score, None);
+            }
         }
 
         let mut best_move = None;
@@ -64,21 +693,75 @@ impl ShadowGuard {
         });
 
         if moves.is_empty() {
-            return (if board.status() == GameStatus::Drawn { 0 } else { -20000 }, None);
+            let score = if board.checkers().is_empty() { self.position_draw(ply) } else { terminal_score(board, ply) };
+            return (score, None);
+        }
+
+        // Root moves get a canonical order, so among equal scores the first one
+        // kept is always the same: best MVV-LVA first, then by squares
+        if ply == 0 {
+            moves.sort_by_key(|&mv| {
+                (std::cmp::Reverse(mvv_lva(board, mv)), mv.from as u8, mv.to as u8, mv.promotion.map(|p| p as u8))
+            });
+            moves.sort_by_key(|mv| !self.root_hints.contains(mv));
+            // A stored iteration of this position knows better
+            if let Some(order) = self.last_iteration.as_ref().filter(|o| o.hash == hash) {
+                moves.sort_by_key(|mv| order.moves.iter().position(|(m, _)| m == mv).unwrap_or(usize::MAX));
+            }
+        }
+
+        // Try the cached best move first
+        if let Some(tt_move) = self.tt.get(hash).and_then(|e| e.best_move) {
+            if let Some(i) = moves.iter().position(|&mv| mv == tt_move) {
+                moves.swap(0, i);
+            }
         }
 
+        let alpha_orig = alpha;
+        let path_draws_before = self.path_draws;
+        let mut cutoff = false;
+        self.history.push(hash);
         for mv in moves {
             let mut next_board = board.clone();
             next_board.play(mv);
-            let (score, _) = self.search_with_move(&next_board, depth - 1, -beta, -alpha);
+            // Sampling needs exact scores for every move within the margin
+            let floor = if ply == 0 && self.temperature > 0.0 { alpha - self.temperature_margin } else { alpha };
+            if let Some(recorder) = self.recorder.as_mut() {
+                recorder.enter(Some(mv), depth - 1, -beta, -floor);
+            }
+            let (score, _) = self.search_node(&next_board, material.after_move(board, mv), depth - 1, ply + 1, -beta, -floor);
+            if let Some(recorder) = self.recorder.as_mut() {
+                recorder.exit(score);
+            }
+            if self.stopped { break; }
             let score = -score;
-            if score >= beta { return (beta, Some(mv)); }
+            if ply == 0 {
+                self.root_scores.push((mv, score));
+            }
+            if score >= beta {
+                best_score = beta;
+                best_move = Some(mv);
+                cutoff = true;
+                break;
+            }
             if score > alpha {
                 alpha = score;
                 best_score = score;
                 best_move = Some(mv);
             }
         }
+        self.history.pop();
+
+        if !self.stopped && self.path_draws == path_draws_before {
+            let (score, bound) = if cutoff {
+                (beta, Bound::Lower)
+            } else if best_score > alpha_orig {
+                (best_score, Bound::Exact)
+            } else {
+                (alpha_orig, Bound::Upper)
+            };
+            self.tt.store(hash, depth, score, bound, best_move, ply);
+        }
         (best_score, best_move)
     }
 
@@ -86,17 +769,39 @@ impl ShadowGuard {
         self.search_with_move(board, depth, alpha, beta).0
     }
 
-    fn quiescence(&mut self, board: &Board, mut alpha: i32, beta: i32) -> i32 {
-        let stand_pat = self.eval(board);
+    /// Capture-only search; `qdepth` counts down and stand pat is final at 0.
+    /// A side in check with no legal move is mated here too, not stood pat.
+    fn quiescence(&mut self, board: &Board, material: Material, qdepth: i32, ply: i32, mut alpha: i32, beta: i32) -> i32 {
+        self.nodes += 1;
+        if self.should_stop() {
+            return alpha;
+        }
+        self.seldepth = self.seldepth.max(ply);
+        if ply >= self.max_ply {
+            return self.eval(board, material);
+        }
+        if !board.checkers().is_empty() && !board.generate_moves(|_| true) {
+            return terminal_score(board, ply);
+        }
+        if is_insufficient_material(board) {
+            return self.position_draw(ply);
+        }
+        let stand_pat = self.eval(board, material);
         if stand_pat >= beta { return beta; }
         if stand_pat > alpha { alpha = stand_pat; }
+        if qdepth <= 0 { return alpha; }
 
         let mut moves = Vec::new();
-        let occupied = board.occupied();
+        let enemies = board.colors(!board.side_to_move());
         board.generate_moves(|mut mvs| {
-            mvs.to &= occupied;
+            // Pawns keep their empty targets so en passant survives the mask
+            if mvs.piece != Piece::Pawn {
+                mvs.to &= enemies;
+            }
             for mv in mvs {
-                moves.push(mv);
+                if is_capture(board, mv) {
+                    moves.push(mv);
+                }
             }
             false
         });
@@ -104,33 +809,447 @@ impl ShadowGuard {
         for mv in moves {
             let mut next_board = board.clone();
             next_board.play(mv);
-            let score = -self.quiescence(&next_board, -beta, -alpha);
+            let score = -self.quiescence(&next_board, material.after_move(board, mv), qdepth - 1, ply + 1, -beta, -alpha);
+            if self.stopped { break; }
             if score >= beta { return beta; }
             if score > alpha { alpha = score; }
         }
         alpha
     }
 
-    fn eval(&self, board: &Board) -> i32 {
-        let mut score = 0;
-        let us = board.side_to_move();
-        let them = !us;
+    fn eval(&self, board: &Board, material: Material) -> i32 {
+        let mut score = material.relative(board.side_to_move()) + endgame_king_term(board);
+        if self.passed_pawn_weight != 0.0 {
+            score += (self.passed_pawn_weight * passed_pawn_score(board) as f32) as i32;
+        }
+        score
+    }
+}
 
-        let pieces = [
-            (Piece::Pawn, 100),
-            (Piece::Knight, 320),
-            (Piece::Bishop, 330),
-            (Piece::Rook, 500),
-            (Piece::Queen, 900),
-        ];
+/// True if the side to move can checkmate at once.
+fn has_mate_in_one(board: &Board) -> bool {
+    let mut found = false;
+    board.generate_moves(|mvs| {
+        found = mvs.into_iter().any(|mv| {
+            let mut next_board = board.clone();
+            next_board.play(mv);
+            next_board.status() == GameStatus::Won
+        });
+        found
+    });
+    found
+}
+
+fn wdl_score(wdl: Wdl) -> i32 {
+    match wdl {
+        Wdl::Win => TB_WIN_SCORE,
+        Wdl::Draw => 0,
+        Wdl::Loss => -TB_WIN_SCORE,
+    }
+}
 
-        let our_color = board.colors(us);
-        let their_color = board.colors(them);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        for (p, val) in pieces {
-            score += (board.pieces(p) & our_color).len() as i32 * val;
-            score -= (board.pieces(p) & their_color).len() as i32 * val;
+    #[test]
+    fn contempt_draws_are_not_stored() {
+        // Nxa2 leaves a lone knight: a draw the side behind in material welcomes
+        let board: Board = "4k3/8/8/8/8/8/r7/2N1K3 w - - 0 1".parse().unwrap();
+        let mut shadow = ShadowGuard::new();
+        assert_ne!(shadow.contempt_for(&board), 0);
+        shadow.search_with_move(&board, 2, -30000, 30000);
+        assert!(shadow.tt.get(board.hash()).is_none());
+
+        let mut shadow = ShadowGuard::new();
+        shadow.contempt_factor = 0.0;
+        shadow.search_with_move(&board, 2, -30000, 30000);
+        assert!(shadow.tt.get(board.hash()).is_some());
+    }
+
+    #[test]
+    fn stop_only_ends_the_search_it_hit() {
+        let board = Board::default();
+        let mut shadow = ShadowGuard::new();
+        shadow.stop_handle().store(true, Ordering::Relaxed);
+        let (_, best) = shadow.search_with_move(&board, 4, -30000, 30000);
+        assert!(best.is_some());
+        // Polled every STOP_POLL_INTERVAL nodes, so a stale stop would end this early
+        assert!(shadow.nodes > STOP_POLL_INTERVAL);
+        assert!(!shadow.stop.load(Ordering::Relaxed));
+    }
+
+    /// Log sink the test can still read after handing it to the guard.
+    #[derive(Clone, Default)]
+    struct SharedLog(std::rc::Rc<std::cell::RefCell<Vec<u8>>>);
+
+    impl Write for SharedLog {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().extend_from_slice(buf);
+            Ok(buf.len())
         }
-        score
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn iterations_log_one_line() {
+        let log = SharedLog::default();
+        let mut shadow = ShadowGuard::new().with_logger(log.clone());
+        let mut depths = Vec::new();
+        shadow.search_iterations(&Board::default(), 4, |_, depth, _| depths.push(depth));
+        assert_eq!(depths, [1, 2, 3, 4]);
+        assert_eq!(log.0.borrow().iter().filter(|&&b| b == b'\n').count(), 1);
+    }
+
+    #[test]
+    fn probes_leave_the_recorded_tree() {
+        let board = Board::default();
+        let mut shadow = ShadowGuard::new();
+        shadow.recorder = Some(TreeRecorder::new());
+        shadow.search_with_move(&board, 2, -30000, 30000);
+        let tree = shadow.recorder.as_ref().unwrap().to_json();
+        // A check, so the probe runs its search instead of the quiet pre-screen
+        let philidor: Board = "rnbqkbnr/ppp2ppp/3p4/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 0 3".parse().unwrap();
+        shadow.probe_tactics(&philidor, "f1b5".parse().unwrap());
+        shadow.threat_map(&board, 1);
+        shadow.best_safe_move(&board, 1);
+        shadow.detect_fortress(&board, 2);
+        assert_eq!(shadow.recorder.as_ref().unwrap().to_json(), tree);
+    }
+
+    #[test]
+    fn lost_positions_resign_and_balanced_ones_play() {
+        // A bare king against king and queen, with nothing to take
+        let lost: Board = "7k/8/8/8/8/8/8/KQ6 b - - 0 1".parse().unwrap();
+        let mut shadow = ShadowGuard::new();
+        let verdicts: Vec<_> = (0..shadow.adjudicator.sustain).map(|_| shadow.adjudicate(&lost, 2)).collect();
+        assert_eq!(verdicts.last(), Some(&Adjudication::Resign));
+        // One score past the threshold is a blip, not a verdict
+        assert!(verdicts[..verdicts.len() - 1].iter().all(|&v| v == Adjudication::Play));
+
+        let mut shadow = ShadowGuard::new();
+        for _ in 0..shadow.adjudicator.sustain {
+            assert_eq!(shadow.adjudicate(&Board::default(), 2), Adjudication::Play);
+        }
+    }
+
+    #[test]
+    fn adjudication_is_a_fresh_unrecorded_search() {
+        let board = Board::default();
+        let mut shadow = ShadowGuard::new();
+        shadow.recorder = Some(TreeRecorder::new());
+        shadow.search_with_move(&board, 2, -30000, 30000);
+        let tree = shadow.recorder.as_ref().unwrap().to_json();
+        shadow.stop_handle().store(true, Ordering::Relaxed);
+        shadow.tt.clear();
+        shadow.adjudicate(&board, 4);
+        // A stale stop would end the search at the first poll
+        assert!(shadow.nodes > STOP_POLL_INTERVAL);
+        assert_eq!(shadow.recorder.as_ref().unwrap().to_json(), tree);
+        let nodes = shadow.nodes;
+        shadow.tt.clear();
+        shadow.adjudicate(&board, 4);
+        assert_eq!(shadow.nodes, nodes);
+    }
+
+    #[test]
+    fn nodes_count_one_search() {
+        let board = Board::default();
+        let mut shadow = ShadowGuard::new();
+        shadow.search_with_move(&board, 3, -30000, 30000);
+        let first = shadow.nodes;
+        shadow.tt.clear();
+        shadow.search_with_move(&board, 3, -30000, 30000);
+        assert_eq!(shadow.nodes, first);
+    }
+
+    #[test]
+    fn stalemate_is_a_draw_and_mate_counts_plies() {
+        let stalemate: Board = "7k/5Q2/6K1/8/8/8/8/8 b - - 0 1".parse().unwrap();
+        let mated: Board = "6Qk/8/6K1/8/8/8/8/8 b - - 0 1".parse().unwrap();
+        assert_eq!(terminal_score(&stalemate, 3), 0);
+        assert_eq!(terminal_score(&mated, 3), -(MATE_SCORE - 3));
+        // Seen from the mating side one ply up, a quicker mate scores higher
+        let mate_in_one: Board = "7k/Q7/6K1/8/8/8/8/8 w - - 0 1".parse().unwrap();
+        let (score, best) = ShadowGuard::new().search_with_move(&mate_in_one, 2, -30000, 30000);
+        assert_eq!(score, MATE_SCORE - 1);
+        let mut mated = mate_in_one.clone();
+        mated.play(best.unwrap());
+        assert_eq!(terminal_score(&mated, 1), -(MATE_SCORE - 1));
+    }
+
+    #[test]
+    fn quiescence_sees_mate_instead_of_standing_pat() {
+        // Back-rank mate with Black a rook and two knights up
+        let board: Board = "R5k1/5ppp/8/8/7r/K7/7n/7n b - - 0 1".parse().unwrap();
+        assert!(!board.generate_moves(|_| true));
+        let mut shadow = ShadowGuard::new();
+        let material = Material::from_board(&board);
+        assert!(shadow.eval(&board, material) > 0);
+        for qdepth in [0, DEFAULT_QDEPTH] {
+            assert_eq!(shadow.quiescence(&board, material, qdepth, 5, -30000, 30000), -(MATE_SCORE - 5));
+        }
+    }
+
+    #[test]
+    fn hundred_quiet_plies_are_a_draw() {
+        let mut shadow = ShadowGuard::new();
+        shadow.contempt_factor = 0.0;
+        // A queen up, but every move is the hundredth without a capture or pawn move
+        let fresh: Board = "4k3/8/8/8/8/8/8/Q3K3 w - - 0 80".parse().unwrap();
+        let stale: Board = "4k3/8/8/8/8/8/8/Q3K3 w - - 99 80".parse().unwrap();
+        assert!(shadow.search_with_move(&fresh, 2, -30000, 30000).0 > 500);
+        shadow.tt.clear();
+        assert_eq!(shadow.search_with_move(&stale, 2, -30000, 30000).0, 0);
+        // Mate on the hundredth ply still counts
+        let mate: Board = "7k/Q7/6K1/8/8/8/8/8 w - - 99 80".parse().unwrap();
+        assert_eq!(shadow.search_with_move(&mate, 2, -30000, 30000).0, MATE_SCORE - 1);
+    }
+
+    #[test]
+    fn illegal_moves_are_vetoed_unsearched() {
+        let mut shadow = ShadowGuard::new();
+        // Legal in the Philidor, not from the start position
+        let feedback = shadow.probe_tactics(&Board::default(), "f1b5".parse().unwrap());
+        assert!(!feedback.is_safe);
+        assert_eq!(feedback.score, -MATE_SCORE);
+        assert_eq!(shadow.nodes, 0);
+        assert!(!shadow.verify_move(&Board::default(), "e2e5".parse().unwrap()));
+    }
+
+    #[test]
+    fn stop_from_another_thread_ends_the_search() {
+        let board = Board::default();
+        let mut shadow = ShadowGuard::new();
+        let stop = shadow.stop_handle();
+        let start = std::time::Instant::now();
+        let stopper = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            stop.store(true, Ordering::Relaxed);
+        });
+        // Far deeper than could finish on its own
+        let (_, best) = shadow.search_iterations(&board, MAX_ITERATIVE_DEPTH, |_, _, _| {});
+        stopper.join().unwrap();
+        assert!(start.elapsed() < std::time::Duration::from_secs(5), "took {:?}", start.elapsed());
+        assert!(board.is_legal(best.unwrap()));
+    }
+
+    /// Tablebase that calls every position a draw except one, lost for its
+    /// side to move.
+    struct OneWinTablebase(u64);
+
+    impl Tablebase for OneWinTablebase {
+        fn probe_wdl(&self, board: &Board) -> Option<Wdl> {
+            Some(if board.hash() == self.0 { Wdl::Loss } else { Wdl::Draw })
+        }
+    }
+
+    #[test]
+    fn tablebase_results_override_the_search() {
+        let board: Board = "4k3/8/8/8/8/8/8/Q3K3 w - - 0 1".parse().unwrap();
+        // A queen up, but the tablebase only knows one winning move: a king retreat
+        let retreat: Move = "e1d1".parse().unwrap();
+        let mut winning = board.clone();
+        winning.play(retreat);
+        let mut shadow = ShadowGuard::new();
+        shadow.tb_pieces = 3;
+        shadow.tablebase = Box::new(OneWinTablebase(winning.hash()));
+        assert_eq!(shadow.search_with_move(&board, 4, -30000, 30000), (TB_WIN_SCORE, Some(retreat)));
+        assert_eq!(shadow.nodes, 0);
+
+        // Everything drawn: the extra queen is worth nothing
+        shadow.tablebase = Box::new(OneWinTablebase(0));
+        assert_eq!(shadow.search_with_move(&board, 4, -30000, 30000).0, 0);
+        // Above `tb_pieces` the tablebase is not consulted
+        shadow.tb_pieces = 2;
+        assert!(shadow.search_with_move(&board, 2, -30000, 30000).0 > 500);
+    }
+
+    #[test]
+    fn quiet_safe_moves_skip_the_probe_search() {
+        let mut shadow = ShadowGuard::new();
+        let feedback = shadow.probe_tactics(&Board::default(), "e2e4".parse().unwrap());
+        assert!(feedback.is_safe && feedback.danger_squares.is_empty());
+        assert_eq!(shadow.nodes, 0);
+        // Putting the knight where the e5 pawn takes it is searched
+        let board: Board = "4k3/8/8/4p3/8/8/4N3/4K3 w - - 0 1".parse().unwrap();
+        shadow.probe_tactics(&board, "e2c3".parse().unwrap());
+        assert_eq!(shadow.nodes, 0);
+        shadow.probe_tactics(&board, "e2d4".parse().unwrap());
+        assert!(shadow.nodes > 0);
+    }
+
+    #[test]
+    fn mating_moves_are_safe() {
+        let board: Board = "7k/Q7/6K1/8/8/8/8/8 w - - 0 1".parse().unwrap();
+        let mut shadow = ShadowGuard::new();
+        let feedback = shadow.probe_tactics(&board, "a7g7".parse().unwrap());
+        assert!(feedback.is_safe);
+        assert!(feedback.danger_squares.is_empty());
+        assert_eq!(feedback.score, MATE_SCORE - 1);
+        assert_eq!(shadow.nodes, 0);
+    }
+
+    #[test]
+    fn recorded_root_has_a_child_per_legal_move() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        ] {
+            let board: Board = fen.parse().unwrap();
+            let mut legal = 0;
+            board.generate_moves(|mvs| {
+                legal += mvs.len();
+                false
+            });
+            let mut shadow = ShadowGuard::new();
+            shadow.recorder = Some(TreeRecorder::new());
+            shadow.search_with_move(&board, 2, -30000, 30000);
+            let recorder = shadow.recorder.as_ref().unwrap();
+            let root = recorder.root().unwrap();
+            assert_eq!(root.children.len(), legal, "{}", fen);
+            assert!(root.children.iter().all(|&child| recorder.nodes[child].score.is_some()));
+        }
+    }
+
+    #[test]
+    fn the_side_ahead_avoids_repeating() {
+        let start: Board = "6k1/5pp1/7p/8/8/8/5PPP/R5K1 w - - 0 1".parse().unwrap();
+        let check: Move = "a1a8".parse().unwrap();
+        let mut shadow = ShadowGuard::new();
+        assert_eq!(shadow.search_iterations(&start, 4, |_, _, _| {}).1, Some(check));
+
+        // Ra8+ Kh7 Ra1 Kg8 brings the start back: the check would repeat it
+        let mut board = start.clone();
+        let mut game = Vec::new();
+        for mv in ["a1a8", "g8h7", "a8a1", "h7g8"] {
+            game.push(board.hash());
+            board.play(mv.parse().unwrap());
+        }
+        assert_eq!(board.hash(), start.hash());
+        let mut shadow = ShadowGuard::new();
+        shadow.set_game_history(&game);
+        let (score, best) = shadow.search_iterations(&board, 4, |_, _, _| {});
+        assert_ne!(best, Some(check));
+        assert!(score > crate::material::piece_value(Piece::Rook) / 2, "{}", score);
+    }
+
+    #[test]
+    fn resuming_skips_the_finished_iterations() {
+        let board: Board = "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3".parse().unwrap();
+        let mut fresh = ShadowGuard::new();
+        fresh.search_iterations(&board, 4, |_, _, _| {});
+
+        let mut resumed = ShadowGuard::new();
+        resumed.search_iterations(&board, 3, |_, _, _| {});
+        resumed.resume(&board, 1);
+        assert_eq!(resumed.last_iteration.as_ref().unwrap().depth, 4);
+        assert!(resumed.nodes < fresh.nodes, "{} vs {}", resumed.nodes, fresh.nodes);
+    }
+
+    #[test]
+    fn shallower_quiescence_searches_fewer_nodes() {
+        // Plenty of captures on the board for quiescence to chase
+        let board: Board = "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4".parse().unwrap();
+        let nodes = |qdepth| {
+            let mut shadow = ShadowGuard::new();
+            shadow.qdepth = qdepth;
+            shadow.search_with_move(&board, 3, -30000, 30000);
+            shadow.nodes
+        };
+        assert!(nodes(1) < nodes(DEFAULT_QDEPTH), "{} vs {}", nodes(1), nodes(DEFAULT_QDEPTH));
+        assert!(nodes(0) <= nodes(1));
+    }
+
+    #[test]
+    fn extreme_depths_stop_at_max_ply() {
+        let board: Board = "8/8/4k3/8/8/3K4/4P3/8 w - - 0 1".parse().unwrap();
+        for (depth, max_ply) in [(1000, 6), (i32::MAX, DEFAULT_MAX_PLY)] {
+            let mut shadow = ShadowGuard::new();
+            shadow.max_ply = max_ply;
+            shadow.node_limit = 200_000;
+            let (_, best) = shadow.search_with_move(&board, depth, -30000, 30000);
+            assert!(board.is_legal(best.unwrap()));
+            assert!(shadow.seldepth <= max_ply, "seldepth {} past {}", shadow.seldepth, max_ply);
+            // Lines run into the lowered cap within the node limit
+            if max_ply < DEFAULT_MAX_PLY {
+                assert_eq!(shadow.seldepth, max_ply);
+            }
+        }
+    }
+
+    #[test]
+    fn self_play_logs_one_line_per_move() {
+        let log = SharedLog::default();
+        let mut shadow = ShadowGuard::new().with_logger(log.clone());
+        let mut board = Board::default();
+        let mut played = Vec::new();
+        for _ in 0..4 {
+            let (score, best) = shadow.search_with_move(&board, 2, -30000, 30000);
+            let mv = best.unwrap();
+            // Vetting the move is not a decision of its own
+            assert!(shadow.probe_tactics(&board, mv).score > -MATE_SCORE);
+            played.push((board.clone(), score));
+            board.play(mv);
+        }
+        let text = String::from_utf8(log.0.borrow().clone()).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), played.len());
+        for (line, (position, score)) in lines.iter().zip(&played) {
+            let fields: Vec<&str> = line.rsplitn(3, ',').collect();
+            assert_eq!(fields[0].parse::<i32>().unwrap(), *score);
+            fields[1].parse::<i32>().unwrap();
+            assert_eq!(fields[2].parse::<Board>().unwrap(), *position);
+        }
+    }
+
+    #[test]
+    fn safe_moves_keep_the_queen() {
+        // Qxc5 takes a loose pawn, which one ply ranks first, but Nd3+ forks king and queen
+        let board: Board = "4k2r/pp4pp/8/2p1n3/8/8/PP4PP/2Q1K3 w - - 0 1".parse().unwrap();
+        let greedy: Move = "c1c5".parse().unwrap();
+        let mut shadow = ShadowGuard::new();
+        assert!(!shadow.verify_move(&board, greedy));
+
+        let best = shadow.best_safe_move(&board, 1).unwrap();
+        assert_ne!(best, greedy);
+        assert!(shadow.verify_move(&board, best));
+    }
+
+    #[test]
+    fn sampling_stays_near_the_best() {
+        let moves: Vec<(Move, i32)> = [("e2e4", 40), ("d2d4", 35), ("g1f3", 30), ("a2a3", -20), ("g2g4", -60)]
+            .iter()
+            .map(|&(mv, score)| (mv.parse().unwrap(), score))
+            .collect();
+        let mut rng = XorShift64::new(163);
+        for _ in 0..100 {
+            assert_eq!(sample_near_best(&moves, 0.0, 1000, &mut rng), moves[0]);
+        }
+        let mut seen = Vec::new();
+        for _ in 0..1000 {
+            let pick = sample_near_best(&moves, 20.0, 10, &mut rng);
+            assert!(pick.1 >= moves[0].1 - 10, "{:?} outside the margin", pick);
+            if !seen.contains(&pick) {
+                seen.push(pick);
+            }
+        }
+        // Hot enough that every move in the window comes up
+        assert_eq!(seen.len(), 3);
+    }
+
+    #[test]
+    fn locked_pawns_make_a_fortress() {
+        let fortress = |fen: &str| ShadowGuard::new().detect_fortress(&fen.parse().unwrap(), 6);
+        // A bishop up, but the chains are locked and it can only shuffle
+        assert!(fortress("8/8/1k6/p1p1p1p1/P1P1P1P1/1K6/8/5B2 w - - 0 1"));
+        // A rook gets in behind the pawns and the score keeps climbing
+        assert!(!fortress("8/8/1k6/p1p1p1p1/P1P1P1P1/1K6/8/5R2 w - - 0 1"));
+        // Flat but level, and won but pawnless
+        assert!(!fortress("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"));
+        assert!(!fortress("7k/8/8/8/8/8/8/KQ6 w - - 0 1"));
     }
 }
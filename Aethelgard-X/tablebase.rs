@@ -0,0 +1,37 @@
+use cozy_chess::*;
+
+/// Win/draw/loss from the point of view of the side to move.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Wdl {
+    Loss,
+    Draw,
+    Win,
+}
+
+impl Wdl {
+    pub fn flip(self) -> Self {
+        match self {
+            Wdl::Loss => Wdl::Win,
+            Wdl::Draw => Wdl::Draw,
+            Wdl::Win => Wdl::Loss,
+        }
+    }
+}
+
+/// Endgame tablebase (e.g. Syzygy) consulted by the search once few pieces remain.
+/// Both probes default to "not found", so implementors only override what they have.
+pub trait Tablebase {
+    fn probe_wdl(&self, _board: &Board) -> Option<Wdl> {
+        None
+    }
+
+    /// Distance to zeroing (capture or pawn move), signed like the WDL result.
+    fn probe_dtz(&self, _board: &Board) -> Option<i32> {
+        None
+    }
+}
+
+/// Default backend: never has an answer.
+pub struct NoTablebase;
+
+impl Tablebase for NoTablebase {}
@@ -0,0 +1,107 @@
+use cozy_chess::*;
+
+/// One recorded alpha-beta node. `score` is what the node returned, from its
+/// own side to move (negate it for the parent); None if it never returned.
+#[derive(Clone, Debug)]
+pub struct TreeNode {
+    /// Move leading here from the parent; None at the root
+    pub mv: Option<Move>,
+    pub depth: i32,
+    pub alpha: i32,
+    pub beta: i32,
+    pub score: Option<i32>,
+    /// Indices into `TreeRecorder::nodes`, in search order
+    pub children: Vec<usize>,
+}
+
+/// Records the main-search tree of a `ShadowGuard` search (see its `recorder`
+/// field). Quiescence is not recorded: a depth-0 node is a leaf here. Every
+/// node is kept, so only enable it for shallow debugging searches.
+#[derive(Clone, Debug, Default)]
+pub struct TreeRecorder {
+    /// Node 0 is the root once a search has started
+    pub nodes: Vec<TreeNode>,
+    /// Nodes entered but not yet exited, innermost last
+    open: Vec<usize>,
+}
+
+impl TreeRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drops the previous tree and opens the root of a new one.
+    pub fn start(&mut self, depth: i32, alpha: i32, beta: i32) {
+        self.nodes.clear();
+        self.open.clear();
+        self.enter(None, depth, alpha, beta);
+    }
+
+    /// Opens a child of the innermost open node.
+    pub fn enter(&mut self, mv: Option<Move>, depth: i32, alpha: i32, beta: i32) {
+        let index = self.nodes.len();
+        self.nodes.push(TreeNode { mv, depth, alpha, beta, score: None, children: Vec::new() });
+        if let Some(&parent) = self.open.last() {
+            self.nodes[parent].children.push(index);
+        }
+        self.open.push(index);
+    }
+
+    /// Closes the innermost open node with the score it returned.
+    pub fn exit(&mut self, score: i32) {
+        if let Some(index) = self.open.pop() {
+            self.nodes[index].score = Some(score);
+        }
+    }
+
+    pub fn root(&self) -> Option<&TreeNode> {
+        self.nodes.first()
+    }
+
+    /// The tree as nested JSON objects: `move` (UCI, null at the root),
+    /// `depth`, `alpha`, `beta`, `score` (null if unfinished) and `children`.
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        match self.root() {
+            Some(_) => self.write_json(0, &mut json),
+            None => json.push_str("null"),
+        }
+        json
+    }
+
+    fn write_json(&self, index: usize, json: &mut String) {
+        let node = &self.nodes[index];
+        let mv = node.mv.map_or("null".to_string(), |mv| format!("\"{}\"", mv));
+        let score = node.score.map_or("null".to_string(), |s| s.to_string());
+        *json += &format!(
+            "{{\"move\":{},\"depth\":{},\"alpha\":{},\"beta\":{},\"score\":{},\"children\":[",
+            mv, node.depth, node.alpha, node.beta, score
+        );
+        for (i, &child) in node.children.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            self.write_json(child, json);
+        }
+        json.push_str("]}");
+    }
+
+    /// The tree as a Graphviz digraph, one `n<index>` vertex per node labelled
+    /// with its move, depth, window and score.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph search {\n");
+        for (i, node) in self.nodes.iter().enumerate() {
+            let mv = node.mv.map_or("root".to_string(), |mv| mv.to_string());
+            let score = node.score.map_or("?".to_string(), |s| s.to_string());
+            dot += &format!(
+                "  n{} [label=\"{}\\nd={} [{}, {}]\\n{}\"];\n",
+                i, mv, node.depth, node.alpha, node.beta, score
+            );
+            for &child in &node.children {
+                dot += &format!("  n{} -> n{};\n", i, child);
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
@@ -0,0 +1,191 @@
+use cozy_chess::*;
+
+/// Scores beyond this are mates and get re-based on ply when stored/probed
+const MATE_BOUND: i32 = 19000;
+/// Deepest line the path history can hold
+pub const MAX_PLY: usize = 256;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bound {
+    Exact,
+    /// Score is at least this (beta cutoff)
+    Lower,
+    /// Score is at most this (fail low)
+    Upper,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TtEntry {
+    pub hash: u64,
+    pub depth: i32,
+    pub score: i32,
+    pub bound: Bound,
+    pub best_move: Option<Move>,
+}
+
+/// Depth-indexed transposition table: an entry only answers searches of equal
+/// or lower depth. Always-replace, one entry per slot. Keyed by `Board::hash`,
+/// which covers castling rights and the en passant square as well as placement.
+pub struct TranspositionTable {
+    entries: Vec<Option<TtEntry>>,
+}
+
+impl TranspositionTable {
+    pub fn new(size_log2: u32) -> Self {
+        Self { entries: vec![None; 1 << size_log2] }
+    }
+
+    fn slot(&self, hash: u64) -> usize {
+        (hash as usize) & (self.entries.len() - 1)
+    }
+
+    pub fn get(&self, hash: u64) -> Option<&TtEntry> {
+        self.entries[self.slot(hash)].as_ref().filter(|e| e.hash == hash)
+    }
+
+    /// Fail-hard cutoff score for a node of `depth` at `ply`, if the entry allows one.
+    pub fn probe(&self, hash: u64, depth: i32, alpha: i32, beta: i32, ply: i32) -> Option<i32> {
+        let entry = self.get(hash)?;
+        if entry.depth < depth {
+            return None;
+        }
+        let score = score_from_tt(entry.score, ply);
+        match entry.bound {
+            Bound::Exact => Some(score.clamp(alpha, beta)),
+            Bound::Lower if score >= beta => Some(beta),
+            Bound::Upper if score <= alpha => Some(alpha),
+            _ => None,
+        }
+    }
+
+    pub fn store(&mut self, hash: u64, depth: i32, score: i32, bound: Bound, best_move: Option<Move>, ply: i32) {
+        let slot = self.slot(hash);
+        self.entries[slot] = Some(TtEntry { hash, depth, score: score_to_tt(score, ply), bound, best_move });
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.fill(None);
+    }
+}
+
+/// Mates are stored relative to the node, not the root, so they stay valid
+/// when the same position is reached at a different ply.
+fn score_to_tt(score: i32, ply: i32) -> i32 {
+    if score > MATE_BOUND { score + ply } else if score < -MATE_BOUND { score - ply } else { score }
+}
+
+fn score_from_tt(score: i32, ply: i32) -> i32 {
+    if score > MATE_BOUND { score - ply } else if score < -MATE_BOUND { score + ply } else { score }
+}
+
+/// Hashes of the positions on the current line (game prefix + search path),
+/// used for repetition detection.
+pub struct PathHistory {
+    hashes: Vec<u64>,
+    /// Length of the game prefix seeded before the search
+    base: usize,
+}
+
+impl Default for PathHistory {
+    fn default() -> Self {
+        Self { hashes: Vec::with_capacity(MAX_PLY), base: 0 }
+    }
+}
+
+impl PathHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Seeds the positions played before the search root (oldest first).
+    pub fn set_game(&mut self, hashes: &[u64]) {
+        self.hashes.clear();
+        self.hashes.extend_from_slice(hashes);
+        self.base = hashes.len();
+    }
+
+    /// Drops any search path, keeping the game prefix.
+    pub fn reset_path(&mut self) {
+        self.hashes.truncate(self.base);
+    }
+
+    pub fn push(&mut self, hash: u64) {
+        self.hashes.push(hash);
+    }
+
+    pub fn pop(&mut self) {
+        self.hashes.pop();
+    }
+
+    /// True if `hash` already occurred with the same side to move since the last
+    /// irreversible move (`halfmove_clock` bounds how far back to look).
+    pub fn is_repetition(&self, hash: u64, halfmove_clock: u8) -> bool {
+        let window = (halfmove_clock as usize).min(self.hashes.len());
+        self.hashes
+            .iter()
+            .rev()
+            .take(window)
+            .skip(1)
+            .step_by(2)
+            .any(|&h| h == hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shadow::MATE_SCORE;
+
+    #[test]
+    fn mates_are_rebased_on_the_probing_ply() {
+        let mut tt = TranspositionTable::new(8);
+        // Mate five plies from the root, found at ply 3: mate in two from the node
+        tt.store(42, 4, MATE_SCORE - 5, Bound::Exact, None, 3);
+        assert_eq!(tt.get(42).unwrap().score, MATE_SCORE - 2);
+        // The same node reached at ply 1 is a mate in three from the root
+        assert_eq!(tt.probe(42, 4, -30000, 30000, 1), Some(MATE_SCORE - 3));
+
+        tt.store(43, 4, -(MATE_SCORE - 6), Bound::Exact, None, 2);
+        assert_eq!(tt.probe(43, 4, -30000, 30000, 4), Some(-(MATE_SCORE - 8)));
+        // Ordinary scores are stored as they are
+        tt.store(44, 4, 120, Bound::Exact, None, 7);
+        assert_eq!(tt.probe(44, 4, -30000, 30000, 1), Some(120));
+    }
+
+    #[test]
+    fn shallow_entries_do_not_answer_deeper_searches() {
+        let mut tt = TranspositionTable::new(8);
+        tt.store(42, 4, 50, Bound::Exact, None, 0);
+        assert_eq!(tt.probe(42, 5, -100, 100, 0), None);
+        assert_eq!(tt.probe(42, 4, -100, 100, 0), Some(50));
+        assert_eq!(tt.probe(42, 2, -100, 100, 0), Some(50));
+        // Bounds only cut when they fall outside the window
+        tt.store(42, 4, 50, Bound::Lower, None, 0);
+        assert_eq!(tt.probe(42, 4, -100, 100, 0), None);
+        assert_eq!(tt.probe(42, 4, -100, 40, 0), Some(40));
+        tt.store(42, 4, 50, Bound::Upper, None, 0);
+        assert_eq!(tt.probe(42, 4, 60, 100, 0), Some(60));
+        // Another position in the same slot is not a hit
+        assert!(tt.get(42 + (1 << 8)).is_none());
+    }
+
+    #[test]
+    fn repetitions_reach_into_the_game_prefix() {
+        let mut history = PathHistory::new();
+        // Oldest first; the position after h3 is the search root
+        history.set_game(&[10, 11, 12, 13]);
+        // Same side to move two plies ago
+        assert!(history.is_repetition(12, 4));
+        assert!(history.is_repetition(10, 4));
+        // Other side to move, or behind the last irreversible move
+        assert!(!history.is_repetition(13, 4));
+        assert!(!history.is_repetition(10, 3));
+
+        history.push(20);
+        history.push(21);
+        assert!(history.is_repetition(20, 6));
+        history.reset_path();
+        assert!(!history.is_repetition(20, 6));
+        assert!(history.is_repetition(12, 4));
+    }
+}
@@ -0,0 +1,275 @@
+use cozy_chess::*;
+use crate::material::piece_value;
+
+/// The piece `mv` captures on `board`, if any. Handles en passant (the target
+/// square is empty) and castling, which cozy_chess encodes as king-takes-own-rook.
+pub fn captured_piece(board: &Board, mv: Move) -> Option<Piece> {
+    if board.color_on(mv.to) == Some(!board.side_to_move()) {
+        return board.piece_on(mv.to);
+    }
+    if board.piece_on(mv.from) == Some(Piece::Pawn) && mv.from.file() != mv.to.file() {
+        return Some(Piece::Pawn);
+    }
+    None
+}
+
+pub fn is_capture(board: &Board, mv: Move) -> bool {
+    captured_piece(board, mv).is_some()
+}
+
+/// The 8 symmetries of the square as maps on square indices (identity first,
+/// file mirror second).
+const SYMMETRIES: [fn(usize) -> usize; 8] = [
+    |sq| sq,
+    |sq| sq ^ 7,
+    |sq| sq ^ 56,
+    |sq| sq ^ 63,
+    |sq| (sq % 8) * 8 + sq / 8,
+    |sq| (7 - sq % 8) * 8 + (7 - sq / 8),
+    |sq| (sq % 8) * 8 + (7 - sq / 8),
+    |sq| (7 - sq % 8) * 8 + sq / 8,
+];
+
+/// splitmix64 finalizer
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+/// Position hash that is identical for symmetry-equivalent positions, for eval
+/// caching. Castling rights pin the board to the identity; pawns or an en passant
+/// square allow only the file mirror; otherwise all 8 symmetries of the square are
+/// reduced. Side to move, castling rights and the en passant file are always part
+/// of the key, so positions differing only in those never share an entry.
+pub fn canonical_fingerprint(board: &Board) -> u64 {
+    let castling = Color::ALL.iter().any(|&c| {
+        let rights = board.castle_rights(c);
+        rights.short.is_some() || rights.long.is_some()
+    });
+    let symmetries = if castling {
+        &SYMMETRIES[..1]
+    } else if !board.pieces(Piece::Pawn).is_empty() || board.en_passant().is_some() {
+        &SYMMETRIES[..2]
+    } else {
+        &SYMMETRIES[..]
+    };
+
+    let stm = board.side_to_move() as u64;
+    symmetries
+        .iter()
+        .map(|map| {
+            let mut h = mix(stm + 1);
+            for sq in board.occupied() {
+                let piece = board.piece_on(sq).unwrap() as u64;
+                let color = board.color_on(sq).unwrap() as u64;
+                h ^= mix(((map(sq as usize) as u64) << 4) | (color << 3) | piece);
+            }
+            if let Some(file) = board.en_passant() {
+                h ^= mix(0x100 | map(file as usize) as u64);
+            }
+            // Only reached under the identity, so rook files need no mapping
+            for color in Color::ALL {
+                let rights = board.castle_rights(color);
+                for (long, file) in [(0, rights.short), (1, rights.long)] {
+                    if let Some(file) = file {
+                        h ^= mix(0x200 | (color as u64) << 4 | long << 3 | file as u64);
+                    }
+                }
+            }
+            h
+        })
+        .min()
+        .unwrap()
+}
+
+/// Most Valuable Victim / Least Valuable Attacker ordering key: captures rank by
+/// victim, then by the cheaper attacker; quiet moves score 0.
+pub fn mvv_lva(board: &Board, mv: Move) -> i32 {
+    match captured_piece(board, mv) {
+        Some(victim) => {
+            let attacker = board.piece_on(mv.from).map_or(0, piece_value);
+            piece_value(victim) * 10 - attacker
+        }
+        None => 0,
+    }
+}
+
+/// Pieces of either color in `occupied` that attack `sq`, seeing through
+/// whatever has already left `occupied`.
+fn attackers_to(board: &Board, sq: Square, occupied: BitBoard) -> BitBoard {
+    let queens = board.pieces(Piece::Queen);
+    let pawns = board.pieces(Piece::Pawn);
+    let attackers = (get_bishop_moves(sq, occupied) & (board.pieces(Piece::Bishop) | queens))
+        | (get_rook_moves(sq, occupied) & (board.pieces(Piece::Rook) | queens))
+        | (get_knight_moves(sq) & board.pieces(Piece::Knight))
+        | (get_king_moves(sq) & board.pieces(Piece::King))
+        // A pawn attacks `sq` from where an opposite-colored pawn on `sq` would attack
+        | (get_pawn_attacks(sq, Color::White) & pawns & board.colors(Color::Black))
+        | (get_pawn_attacks(sq, Color::Black) & pawns & board.colors(Color::White));
+    attackers & occupied
+}
+
+/// Static exchange evaluation: material won (centipawns, for the mover) by `mv`
+/// if both sides keep recapturing on its target square with their least
+/// valuable attacker, each free to stop when that stops paying. X-rays behind
+/// the capturers join in; pins and promotions on recapture are ignored. A king
+/// only recaptures onto an undefended square. Quiet moves measure whether the
+/// moved piece can be won; castling is 0.
+pub fn static_exchange_eval(board: &Board, mv: Move) -> i32 {
+    let us = board.side_to_move();
+    let target = mv.to;
+    if board.color_on(target) == Some(us) {
+        return 0;
+    }
+    let mut occupied = board.occupied() ^ mv.from.bitboard();
+    let mut first = captured_piece(board, mv).map_or(0, piece_value);
+    if board.piece_on(target).is_none() && first > 0 {
+        // En passant: the captured pawn stands beside the target, not on it
+        occupied ^= Square::new(target.file(), mv.from.rank()).bitboard();
+    }
+    let mut on_square = match (mv.promotion, board.piece_on(mv.from)) {
+        (Some(promo), _) => {
+            first += piece_value(promo) - piece_value(Piece::Pawn);
+            promo
+        }
+        (None, Some(piece)) => piece,
+        (None, None) => return 0,
+    };
+
+    let mut gain = vec![first];
+    let mut side = !us;
+    loop {
+        let attackers = attackers_to(board, target, occupied) & board.colors(side);
+        let attacker = match Piece::ALL.iter().find_map(|&p| (attackers & board.pieces(p)).next_square().map(|sq| (p, sq))) {
+            Some(found) => found,
+            None => break,
+        };
+        let (piece, from) = attacker;
+        if piece == Piece::King
+            && !(attackers_to(board, target, occupied ^ from.bitboard()) & board.colors(!side)).is_empty()
+        {
+            break;
+        }
+        gain.push(piece_value(on_square) - gain[gain.len() - 1]);
+        on_square = piece;
+        occupied ^= from.bitboard();
+        side = !side;
+    }
+    // Each side either takes the exchange further or stands pat
+    for d in (1..gain.len()).rev() {
+        gain[d - 1] = -(-gain[d - 1]).max(gain[d]);
+    }
+    gain[0]
+}
+
+/// A capture (or any move) that doesn't lose material by `static_exchange_eval`.
+pub fn is_good_capture(board: &Board, mv: Move) -> bool {
+    static_exchange_eval(board, mv) >= 0
+}
+
+/// Captures winning at least this much by SEE make a position non-quiet
+const QUIET_CAPTURE_MARGIN: i32 = 150;
+
+/// True if the side to move has a capture winning at least `margin` by SEE.
+pub fn has_winning_capture(board: &Board, margin: i32) -> bool {
+    let mut found = false;
+    board.generate_moves(|mvs| {
+        // `is_capture` rather than a mask of enemy squares, which would drop en passant
+        found = mvs.into_iter().any(|mv| is_capture(board, mv) && static_exchange_eval(board, mv) >= margin);
+        found
+    });
+    found
+}
+
+/// True when nothing is loose: the side to move isn't in check, has no
+/// capture winning `QUIET_CAPTURE_MARGIN` or more by SEE, and the opponent
+/// would have none either if it were their move (so nothing of ours hangs).
+pub fn is_quiet(board: &Board) -> bool {
+    match board.null_move() {
+        Some(passed) => {
+            !has_winning_capture(board, QUIET_CAPTURE_MARGIN) && !has_winning_capture(&passed, QUIET_CAPTURE_MARGIN)
+        }
+        // In check
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `board` with every piece and the en passant square moved by `map`, if
+    /// that is still a legal position.
+    fn transformed(board: &Board, map: fn(usize) -> usize) -> Option<Board> {
+        let src = BoardBuilder::from_board(board);
+        let mut dst = src.clone();
+        for sq in Square::ALL {
+            *dst.square_mut(Square::index(map(sq as usize))) = src.square(sq);
+        }
+        dst.en_passant = src.en_passant.map(|sq| Square::index(map(sq as usize)));
+        dst.build().ok()
+    }
+
+    #[test]
+    fn pawnless_symmetries_share_a_fingerprint() {
+        let board: Board = "8/8/2k5/8/8/3N4/5K2/6R1 w - - 0 1".parse().unwrap();
+        let fingerprint = canonical_fingerprint(&board);
+        for map in SYMMETRIES {
+            assert_eq!(canonical_fingerprint(&transformed(&board, map).unwrap()), fingerprint);
+        }
+        let black_to_move: Board = "8/8/2k5/8/8/3N4/5K2/6R1 b - - 0 1".parse().unwrap();
+        assert_ne!(canonical_fingerprint(&black_to_move), fingerprint);
+        let other: Board = "8/8/2k5/8/8/4N3/5K2/6R1 w - - 0 1".parse().unwrap();
+        assert_ne!(canonical_fingerprint(&other), fingerprint);
+    }
+
+    #[test]
+    fn pawns_allow_only_the_file_mirror() {
+        let board: Board = "4k3/1p6/8/2P5/8/8/8/4K3 w - - 0 1".parse().unwrap();
+        let fingerprint = canonical_fingerprint(&board);
+        assert_eq!(canonical_fingerprint(&transformed(&board, SYMMETRIES[1]).unwrap()), fingerprint);
+        for map in &SYMMETRIES[2..] {
+            assert_ne!(canonical_fingerprint(&transformed(&board, *map).unwrap()), fingerprint);
+        }
+        // Castling rights pin the board: its mirror is a different position
+        let castling: Board = "4k3/8/8/8/8/8/8/R3K3 w Q - 0 1".parse().unwrap();
+        let mirror: Board = "3k4/8/8/8/8/8/8/3K3R w - - 0 1".parse().unwrap();
+        assert_ne!(canonical_fingerprint(&castling), canonical_fingerprint(&mirror));
+    }
+
+    #[test]
+    fn en_passant_counts_as_a_winning_capture() {
+        let board: Board = "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 2".parse().unwrap();
+        assert!(has_winning_capture(&board, piece_value(Piece::Pawn)));
+    }
+
+    fn see(fen: &str, mv: &str) -> i32 {
+        static_exchange_eval(&fen.parse().unwrap(), mv.parse().unwrap())
+    }
+
+    #[test]
+    fn exchanges_count_defenders_x_rays_and_kings() {
+        // Queen takes a pawn the e6 pawn defends
+        assert_eq!(see("4k3/8/4p3/3p4/8/8/8/3QK3 w - - 0 1", "d1d5"), piece_value(Piece::Pawn) - piece_value(Piece::Queen));
+        // The d1 rook recaptures through the d2 rook once it has gone
+        assert_eq!(see("3rk3/8/8/3p4/8/8/3R4/3RK3 w - - 0 1", "d2d5"), piece_value(Piece::Pawn));
+        assert_eq!(see("3rk3/8/8/3p4/8/8/8/3RK3 w - - 0 1", "d1d5"), piece_value(Piece::Pawn) - piece_value(Piece::Rook));
+        // The king can't take back on a square the c4 bishop covers
+        assert_eq!(see("4k3/5p2/8/6N1/2B5/8/8/4K3 w - - 0 1", "g5f7"), piece_value(Piece::Pawn));
+        assert_eq!(see("4k3/5p2/8/6N1/8/8/8/4K3 w - - 0 1", "g5f7"), piece_value(Piece::Pawn) - piece_value(Piece::Knight));
+    }
+
+    #[test]
+    fn quiet_means_nothing_hangs_either_way() {
+        let quiet = |fen: &str| is_quiet(&fen.parse().unwrap());
+        assert!(quiet("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"));
+        // Our knight on e5 hangs to the d6 pawn, or theirs hangs to us
+        assert!(!quiet("4k3/8/3p4/4N3/8/8/8/4K3 w - - 0 1"));
+        assert!(!quiet("4k3/8/3p4/4N3/8/8/8/4K3 b - - 0 1"));
+        // A defended pawn is not worth the trade
+        assert!(quiet("4k3/8/3p4/4p3/8/8/8/3QK3 w - - 0 1"));
+        // In check only evasions matter
+        assert!(!quiet("4k3/8/8/8/8/8/8/4K2r w - - 0 1"));
+    }
+}